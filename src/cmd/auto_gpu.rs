@@ -0,0 +1,162 @@
+use anyhow::{anyhow, Result};
+use futures_util::future::join_all;
+use reqwest::Client;
+
+use crate::models::SubmissionMode;
+use crate::service;
+
+/// Whether `--gpu` asks the CLI to pick the GPU itself.
+pub fn is_auto(gpu: &str) -> bool {
+    gpu.eq_ignore_ascii_case("auto")
+}
+
+/// `--gpu auto` is for test runs, where any of the leaderboard's GPUs will
+/// do; scored and confirmed runs need the GPU named up front.
+pub fn ensure_test_mode(mode: SubmissionMode) -> Result<()> {
+    if mode == SubmissionMode::Test {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "--gpu auto only works in test mode; pass the GPU to use for {} mode",
+            mode
+        ))
+    }
+}
+
+/// A GPU chosen by `--gpu auto`, with a sentence explaining the choice.
+#[derive(Debug, PartialEq)]
+pub struct GpuChoice {
+    pub gpu: String,
+    pub reason: String,
+}
+
+/// Pick the least loaded GPU for `leaderboard` from the server's candidates,
+/// breaking ties with the `prefer` list.
+pub async fn choose_gpu(
+    client: &Client,
+    leaderboard: &str,
+    prefer: &[String],
+) -> Result<GpuChoice> {
+    let gpus = service::fetch_gpus(client, leaderboard).await?;
+    if gpus.is_empty() {
+        return Err(anyhow!("Leaderboard {} has no GPUs", leaderboard));
+    }
+
+    // A failed queue lookup only makes that GPU less attractive.
+    let depths = join_all(gpus.iter().map(|gpu| async move {
+        service::fetch_runner_queue(client, leaderboard, &gpu.title_text)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|queue| queue.queued_jobs)
+    }))
+    .await;
+
+    let candidates: Vec<(String, Option<i64>)> = gpus
+        .into_iter()
+        .map(|gpu| gpu.title_text)
+        .zip(depths)
+        .collect();
+    pick_least_loaded(&candidates, prefer)
+        .ok_or_else(|| anyhow!("Could not choose a GPU for {}", leaderboard))
+}
+
+/// Lowest known queue wins; GPUs without queue data come last. Ties go to
+/// the earliest entry in `prefer`, then to the server's order.
+fn pick_least_loaded(candidates: &[(String, Option<i64>)], prefer: &[String]) -> Option<GpuChoice> {
+    let preference = |gpu: &str| {
+        prefer
+            .iter()
+            .position(|p| p.eq_ignore_ascii_case(gpu))
+            .unwrap_or(prefer.len())
+    };
+
+    let (index, (gpu, depth)) =
+        candidates
+            .iter()
+            .enumerate()
+            .min_by_key(|(index, (gpu, depth))| {
+                (depth.is_none(), depth.unwrap_or(0), preference(gpu), *index)
+            })?;
+
+    let summary = candidates
+        .iter()
+        .map(|(gpu, depth)| match depth {
+            Some(depth) => format!("{} {}", gpu, depth),
+            None => format!("{} ?", gpu),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let tied = candidates
+        .iter()
+        .enumerate()
+        .any(|(other, (_, other_depth))| other != index && other_depth == depth);
+    let why = match depth {
+        None => "no runner reported its queue".to_string(),
+        Some(depth) if tied && preference(gpu) < prefer.len() => {
+            format!("{} queued, tied and first in --prefer", depth)
+        }
+        Some(depth) if tied => format!("{} queued, tied and listed first", depth),
+        Some(depth) => format!("shortest queue, {} queued", depth),
+    };
+
+    Some(GpuChoice {
+        gpu: gpu.clone(),
+        reason: format!(
+            "Auto-selected GPU {}: {} (queued jobs: {})",
+            gpu, why, summary
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(list: &[(&str, Option<i64>)]) -> Vec<(String, Option<i64>)> {
+        list.iter()
+            .map(|(gpu, depth)| (gpu.to_string(), *depth))
+            .collect()
+    }
+
+    #[test]
+    fn test_auto_gpu_is_test_mode_only() {
+        assert!(ensure_test_mode(SubmissionMode::Test).is_ok());
+        let err = ensure_test_mode(SubmissionMode::Leaderboard).unwrap_err();
+        assert!(err.to_string().contains("leaderboard mode"), "{}", err);
+    }
+
+    #[test]
+    fn test_pick_least_loaded_prefers_shortest_queue() {
+        let choice = pick_least_loaded(
+            &candidates(&[("H100", Some(4)), ("B200", Some(1)), ("A100", None)]),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(choice.gpu, "B200");
+        assert_eq!(
+            choice.reason,
+            "Auto-selected GPU B200: shortest queue, 1 queued (queued jobs: H100 4, B200 1, A100 ?)"
+        );
+    }
+
+    #[test]
+    fn test_pick_least_loaded_breaks_ties_with_prefer() {
+        let list = candidates(&[("H100", Some(2)), ("B200", Some(2)), ("A100", Some(5))]);
+        let prefer = vec!["b200".to_string(), "H100".to_string()];
+        let choice = pick_least_loaded(&list, &prefer).unwrap();
+        assert_eq!(choice.gpu, "B200");
+        assert!(choice.reason.contains("tied and first in --prefer"));
+
+        assert_eq!(pick_least_loaded(&list, &[]).unwrap().gpu, "H100");
+    }
+
+    #[test]
+    fn test_pick_least_loaded_without_queue_data_uses_prefer() {
+        let list = candidates(&[("H100", None), ("MI300", None)]);
+        let choice = pick_least_loaded(&list, &["MI300".to_string()]).unwrap();
+        assert_eq!(choice.gpu, "MI300");
+        assert!(choice.reason.contains("no runner reported its queue"));
+    }
+}
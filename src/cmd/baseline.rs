@@ -0,0 +1,344 @@
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use std::str::FromStr;
+
+use crate::models::{BenchmarkResult, SubmissionDetails, SubmissionRun};
+use crate::service;
+use crate::utils;
+
+/// How many of the user's submissions are scanned for `history-best`.
+const HISTORY_BEST_LOOKBACK_SUBMISSIONS: i32 = 200;
+
+/// What a benchmark run is compared against for `--baseline`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Baseline {
+    /// A previous submission's benchmark (or public leaderboard) run.
+    Submission(i64),
+    /// A geomean score in seconds, as shown on the leaderboard.
+    Score(f64),
+    /// The user's best leaderboard score for this leaderboard and GPU.
+    HistoryBest,
+}
+
+impl FromStr for Baseline {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value.eq_ignore_ascii_case("history-best") {
+            return Ok(Self::HistoryBest);
+        }
+        if let Ok(id) = value.parse::<i64>() {
+            return Ok(Self::Submission(id));
+        }
+        match value.parse::<f64>() {
+            Ok(score) if score > 0.0 && score.is_finite() => Ok(Self::Score(score)),
+            _ => Err(format!(
+                "expected a submission id, a positive geomean score in seconds, or `history-best`, got `{}`",
+                value
+            )),
+        }
+    }
+}
+
+/// A baseline resolved to a geomean (seconds) and, when known, per-shape means.
+#[derive(Debug)]
+pub struct ResolvedBaseline {
+    pub label: String,
+    pub geomean: f64,
+    pub benchmarks: Vec<BenchmarkResult>,
+}
+
+pub async fn resolve_baseline(
+    client: &Client,
+    baseline: &Baseline,
+    leaderboard: &str,
+    gpu: &str,
+) -> Result<ResolvedBaseline> {
+    match baseline {
+        Baseline::Score(score) => Ok(ResolvedBaseline {
+            label: format!("score {} s", score),
+            geomean: *score,
+            benchmarks: Vec::new(),
+        }),
+        Baseline::Submission(id) => {
+            let details = service::get_user_submission(client, *id).await?;
+            let run = baseline_run(&details, gpu).ok_or_else(|| {
+                anyhow!(
+                    "Submission {} has no benchmark or leaderboard run on {}",
+                    id,
+                    gpu
+                )
+            })?;
+            let geomean = benchmark_geomean(&run.benchmarks)
+                .or(run.score)
+                .ok_or_else(|| anyhow!("Submission {} has no timings to compare against", id))?;
+            Ok(ResolvedBaseline {
+                label: format!("submission {} ({} run)", id, run.mode),
+                geomean,
+                benchmarks: run.benchmarks.clone(),
+            })
+        }
+        Baseline::HistoryBest => {
+            let (id, score) = best_leaderboard_score(client, leaderboard, gpu)
+                .await?
+                .ok_or_else(|| {
+                    anyhow!(
+                        "No scored leaderboard submissions found for {} on {}",
+                        leaderboard,
+                        gpu
+                    )
+                })?;
+            // Per-shape timings are a bonus; the score alone is enough to gate on.
+            let benchmarks = service::get_user_submission(client, id)
+                .await
+                .ok()
+                .and_then(|details| baseline_run(&details, gpu).map(|run| run.benchmarks.clone()))
+                .unwrap_or_default();
+            Ok(ResolvedBaseline {
+                label: format!("best leaderboard submission {} (score {} s)", id, score),
+                geomean: score,
+                benchmarks,
+            })
+        }
+    }
+}
+
+/// The user's lowest public leaderboard score on `gpu`, with its submission
+/// id. The submission list does not say which mode a score came from, so its
+/// lowest score per submission only bounds the leaderboard score from below;
+/// submissions are confirmed through their details, cheapest first, until no
+/// remaining one could beat the best found.
+pub async fn best_leaderboard_score(
+    client: &Client,
+    leaderboard: &str,
+    gpu: &str,
+) -> Result<Option<(i64, f64)>> {
+    let submissions = service::get_user_submissions(
+        client,
+        Some(leaderboard),
+        Some(HISTORY_BEST_LOOKBACK_SUBMISSIONS),
+    )
+    .await?;
+    let mut candidates: Vec<(i64, f64)> = submissions
+        .iter()
+        .filter_map(|sub| {
+            sub.runs
+                .iter()
+                .filter(|run| run.gpu_type.eq_ignore_ascii_case(gpu))
+                .filter_map(|run| run.score)
+                .min_by(f64::total_cmp)
+                .map(|score| (sub.id, score))
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let mut best: Option<(i64, f64)> = None;
+    for (id, lower_bound) in candidates {
+        if best.is_some_and(|(_, score)| score <= lower_bound) {
+            break;
+        }
+        let details = service::get_user_submission(client, id).await?;
+        if let Some(score) = leaderboard_score(&details, gpu) {
+            if !best.is_some_and(|(_, best_score)| best_score <= score) {
+                best = Some((id, score));
+            }
+        }
+    }
+    Ok(best)
+}
+
+/// The score of the public leaderboard run of `details` on `gpu`. Secret
+/// runs and other modes do not count toward the leaderboard.
+fn leaderboard_score(details: &SubmissionDetails, gpu: &str) -> Option<f64> {
+    details
+        .runs
+        .iter()
+        .filter(|run| run.runner.eq_ignore_ascii_case(gpu))
+        .filter(|run| run.mode == "leaderboard" && !run.secret)
+        .filter_map(|run| run.score)
+        .min_by(f64::total_cmp)
+}
+
+/// The run of `details` on `gpu` to treat as a baseline: the benchmark run if
+/// there is one, otherwise the public leaderboard run.
+fn baseline_run<'a>(details: &'a SubmissionDetails, gpu: &str) -> Option<&'a SubmissionRun> {
+    let on_gpu = |run: &&SubmissionRun| run.runner.eq_ignore_ascii_case(gpu);
+    details
+        .runs
+        .iter()
+        .filter(on_gpu)
+        .find(|run| run.mode == "benchmark")
+        .or_else(|| {
+            details
+                .runs
+                .iter()
+                .filter(on_gpu)
+                .find(|run| run.mode == "leaderboard" && !run.secret)
+        })
+}
+
+/// The benchmark-mode run of a finished submission on `gpu`.
+pub fn benchmark_run<'a>(details: &'a SubmissionDetails, gpu: &str) -> Option<&'a SubmissionRun> {
+    details
+        .runs
+        .iter()
+        .find(|run| run.mode == "benchmark" && run.runner.eq_ignore_ascii_case(gpu))
+}
+
+/// Geomean of the per-shape means in seconds, the unit leaderboard scores use.
+pub fn benchmark_geomean(benchmarks: &[BenchmarkResult]) -> Option<f64> {
+    let means: Option<Vec<f64>> = benchmarks.iter().map(|b| b.mean).collect();
+    utils::geometric_mean(&means?).map(|nanos| nanos / 1e9)
+}
+
+/// Percent change from `base` to `new`; positive means slower.
+pub fn percent_slower(base: f64, new: f64) -> f64 {
+    (new - base) / base * 100.0
+}
+
+/// Compare a finished benchmark run against the baseline. Returns the report
+/// text and whether the run regressed by more than `threshold_percent`;
+/// without a threshold the comparison is only reported.
+pub fn regression_report(
+    baseline: &ResolvedBaseline,
+    benchmarks: &[BenchmarkResult],
+    threshold_percent: Option<f64>,
+) -> Result<(String, bool)> {
+    let geomean = benchmark_geomean(benchmarks)
+        .ok_or_else(|| anyhow!("The benchmark run reported no timings to compare"))?;
+    let change = percent_slower(baseline.geomean, geomean);
+    let exceeds = |change: f64| threshold_percent.is_some_and(|threshold| change > threshold);
+    let regressed = exceeds(change);
+
+    let mut header = format!(
+        "Geomean {} s vs baseline {}: {:+.2}%",
+        geomean, baseline.label, change
+    );
+    if let Some(threshold) = threshold_percent {
+        header.push_str(&format!(" (threshold {:.2}%)", threshold));
+    }
+    let mut lines = vec![header];
+
+    for benchmark in benchmarks {
+        let Some(base) = baseline
+            .benchmarks
+            .iter()
+            .find(|b| b.spec == benchmark.spec)
+        else {
+            continue;
+        };
+        let (Some(base_mean), Some(new_mean)) = (base.mean, benchmark.mean) else {
+            continue;
+        };
+        let shape_change = percent_slower(base_mean, new_mean);
+        let marker = if exceeds(shape_change) {
+            "  REGRESSION"
+        } else {
+            ""
+        };
+        lines.push(format!(
+            "  {}: {} -> {} ({:+.2}%){}",
+            benchmark.spec,
+            utils::format_duration_ns(base_mean),
+            utils::format_duration_ns(new_mean),
+            shape_change,
+            marker
+        ));
+    }
+
+    Ok((lines.join("\n"), regressed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fixtures::{details, run};
+
+    fn bench(spec: &str, mean: f64) -> BenchmarkResult {
+        BenchmarkResult {
+            spec: spec.to_string(),
+            mean: Some(mean),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_baseline() {
+        assert_eq!("1234".parse(), Ok(Baseline::Submission(1234)));
+        assert_eq!("0.0033".parse(), Ok(Baseline::Score(0.0033)));
+        assert_eq!("History-Best".parse(), Ok(Baseline::HistoryBest));
+        assert!("fastest".parse::<Baseline>().is_err());
+        assert!("-0.5".parse::<Baseline>().is_err());
+    }
+
+    #[test]
+    fn test_benchmark_geomean_is_in_seconds() {
+        let geomean = benchmark_geomean(&[bench("a", 2000.0), bench("b", 8000.0)]).unwrap();
+        assert!((geomean - 4e-6).abs() < 1e-15);
+
+        let mut missing = bench("c", 1.0);
+        missing.mean = None;
+        assert_eq!(benchmark_geomean(&[bench("a", 2000.0), missing]), None);
+    }
+
+    #[test]
+    fn test_leaderboard_score_ignores_secret_and_other_modes() {
+        let details = details(
+            7,
+            vec![
+                run("benchmark", false, Some(0.001)),
+                run("leaderboard", true, Some(0.002)),
+                SubmissionRun {
+                    runner: "H100".to_string(),
+                    ..run("leaderboard", false, Some(0.003))
+                },
+                run("leaderboard", false, Some(0.004)),
+            ],
+        );
+        assert_eq!(leaderboard_score(&details, "b200"), Some(0.004));
+        assert_eq!(leaderboard_score(&details, "A100"), None);
+    }
+
+    #[test]
+    fn test_regression_report_flags_slow_shapes() {
+        let baseline = ResolvedBaseline {
+            label: "submission 1 (benchmark run)".to_string(),
+            geomean: 2e-6,
+            benchmarks: vec![bench("m: 128", 1000.0), bench("m: 256", 4000.0)],
+        };
+
+        let (report, regressed) = regression_report(
+            &baseline,
+            &[bench("m: 128", 1500.0), bench("m: 256", 4000.0)],
+            Some(5.0),
+        )
+        .unwrap();
+
+        assert!(regressed);
+        assert!(report.contains("m: 128: 1.00 µs -> 1.50 µs (+50.00%)  REGRESSION"));
+        assert!(report.ends_with("m: 256: 4.00 µs -> 4.00 µs (+0.00%)"));
+    }
+
+    #[test]
+    fn test_regression_report_passes_within_threshold() {
+        let baseline = ResolvedBaseline {
+            label: "score 0.000002 s".to_string(),
+            geomean: 2e-6,
+            benchmarks: Vec::new(),
+        };
+
+        let (report, regressed) =
+            regression_report(&baseline, &[bench("a", 2040.0)], Some(5.0)).unwrap();
+
+        assert!(!regressed);
+        assert!(report.starts_with("Geomean"));
+        assert!(report.contains("+2.00% (threshold 5.00%)"));
+
+        // Without --fail-if-slower-than any slowdown is only reported.
+        let (report, regressed) =
+            regression_report(&baseline, &[bench("a", 4000.0)], None).unwrap();
+        assert!(!regressed);
+        assert!(report.ends_with("+100.00%"), "{}", report);
+    }
+}
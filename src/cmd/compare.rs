@@ -0,0 +1,499 @@
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::IsTerminal;
+
+use crate::models::SubmissionDetails;
+use crate::service;
+use crate::utils;
+
+/// Output format for `popcorn compare`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum CompareFormat {
+    #[default]
+    Table,
+    Json,
+    Markdown,
+}
+
+/// One benchmark shape present in either submission, aligned by
+/// mode/runner/spec. Times are in nanoseconds.
+#[derive(Debug, Serialize, PartialEq)]
+struct BenchmarkDelta {
+    mode: String,
+    runner: String,
+    secret: bool,
+    spec: String,
+    base_mean: Option<f64>,
+    new_mean: Option<f64>,
+    delta: Option<f64>,
+    relative_delta: Option<f64>,
+    regression: bool,
+}
+
+/// Geomean over the shapes both submissions ran for one mode/runner pair.
+#[derive(Debug, Serialize, PartialEq)]
+struct GeomeanDelta {
+    mode: String,
+    runner: String,
+    secret: bool,
+    shapes: usize,
+    base_geomean: Option<f64>,
+    new_geomean: Option<f64>,
+    relative_delta: Option<f64>,
+    base_score: Option<f64>,
+    new_score: Option<f64>,
+    regression: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Comparison {
+    base_id: i64,
+    new_id: i64,
+    benchmarks: Vec<BenchmarkDelta>,
+    geomeans: Vec<GeomeanDelta>,
+}
+
+pub async fn run_compare(
+    cli_id: String,
+    base_id: i64,
+    new_id: i64,
+    format: CompareFormat,
+) -> Result<()> {
+    let client = service::create_client(Some(cli_id))?;
+    let base = service::get_user_submission(&client, base_id).await?;
+    let new = service::get_user_submission(&client, new_id).await?;
+
+    let comparison = compare_submissions(&base, &new);
+    if comparison.benchmarks.is_empty() {
+        return Err(anyhow!(
+            "Submissions {} and {} have no benchmark results to compare",
+            base_id,
+            new_id
+        ));
+    }
+
+    let output = match format {
+        CompareFormat::Table => render_table(&comparison, std::io::stdout().is_terminal()),
+        CompareFormat::Json => serde_json::to_string_pretty(&comparison)?,
+        CompareFormat::Markdown => render_markdown(&comparison),
+    };
+    println!("{}", output);
+    Ok(())
+}
+
+fn compare_submissions(base: &SubmissionDetails, new: &SubmissionDetails) -> Comparison {
+    let mut benchmarks: Vec<BenchmarkDelta> = Vec::new();
+
+    for (side, details) in [(0, base), (1, new)] {
+        for run in &details.runs {
+            for bench in &run.benchmarks {
+                let existing = benchmarks.iter_mut().find(|d| {
+                    d.mode == run.mode
+                        && d.runner == run.runner
+                        && d.secret == run.secret
+                        && d.spec == bench.spec
+                });
+                let delta = match existing {
+                    Some(delta) => delta,
+                    None => {
+                        benchmarks.push(BenchmarkDelta {
+                            mode: run.mode.clone(),
+                            runner: run.runner.clone(),
+                            secret: run.secret,
+                            spec: bench.spec.clone(),
+                            base_mean: None,
+                            new_mean: None,
+                            delta: None,
+                            relative_delta: None,
+                            regression: false,
+                        });
+                        benchmarks.last_mut().unwrap()
+                    }
+                };
+                if side == 0 {
+                    delta.base_mean = bench.mean;
+                } else {
+                    delta.new_mean = bench.mean;
+                }
+            }
+        }
+    }
+
+    for delta in &mut benchmarks {
+        if let (Some(base_mean), Some(new_mean)) = (delta.base_mean, delta.new_mean) {
+            delta.delta = Some(new_mean - base_mean);
+            delta.relative_delta = relative_change(base_mean, new_mean);
+            delta.regression = new_mean > base_mean;
+        }
+    }
+
+    let mut geomeans: Vec<GeomeanDelta> = Vec::new();
+    for delta in &benchmarks {
+        if geomeans
+            .iter()
+            .any(|g| g.mode == delta.mode && g.runner == delta.runner && g.secret == delta.secret)
+        {
+            continue;
+        }
+        let (base_means, new_means): (Vec<f64>, Vec<f64>) = benchmarks
+            .iter()
+            .filter(|d| {
+                d.mode == delta.mode && d.runner == delta.runner && d.secret == delta.secret
+            })
+            .filter_map(|d| Some((d.base_mean?, d.new_mean?)))
+            .unzip();
+        let base_geomean = utils::geometric_mean(&base_means);
+        let new_geomean = utils::geometric_mean(&new_means);
+        let relative_delta = match (base_geomean, new_geomean) {
+            (Some(b), Some(n)) => relative_change(b, n),
+            _ => None,
+        };
+        geomeans.push(GeomeanDelta {
+            mode: delta.mode.clone(),
+            runner: delta.runner.clone(),
+            secret: delta.secret,
+            shapes: base_means.len(),
+            base_geomean,
+            new_geomean,
+            relative_delta,
+            base_score: run_score(base, &delta.mode, &delta.runner, delta.secret),
+            new_score: run_score(new, &delta.mode, &delta.runner, delta.secret),
+            regression: relative_delta.is_some_and(|r| r > 0.0),
+        });
+    }
+
+    Comparison {
+        base_id: base.id,
+        new_id: new.id,
+        benchmarks,
+        geomeans,
+    }
+}
+
+fn run_score(details: &SubmissionDetails, mode: &str, runner: &str, secret: bool) -> Option<f64> {
+    details
+        .runs
+        .iter()
+        .find(|run| run.mode == mode && run.runner == runner && run.secret == secret)
+        .and_then(|run| run.score)
+}
+
+fn relative_change(base: f64, new: f64) -> Option<f64> {
+    if base == 0.0 {
+        None
+    } else {
+        Some((new - base) / base)
+    }
+}
+
+fn run_label(mode: &str, runner: &str, secret: bool) -> String {
+    if secret {
+        format!("{} (secret) on {}", mode, runner)
+    } else {
+        format!("{} on {}", mode, runner)
+    }
+}
+
+fn format_mean(mean: Option<f64>) -> String {
+    mean.map(utils::format_duration_ns)
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn format_delta(delta: Option<f64>) -> String {
+    match delta {
+        Some(d) if d > 0.0 => format!("+{}", utils::format_duration_ns(d)),
+        Some(d) => utils::format_duration_ns(d),
+        None => "-".to_string(),
+    }
+}
+
+fn format_relative(relative: Option<f64>) -> String {
+    relative
+        .map(|r| format!("{:+.2}%", r * 100.0))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn verdict(relative: Option<f64>) -> &'static str {
+    match relative {
+        Some(r) if r > 0.0 => "slower",
+        Some(r) if r < 0.0 => "faster",
+        Some(_) => "same",
+        None => "-",
+    }
+}
+
+fn render_table(comparison: &Comparison, color: bool) -> String {
+    let paint = |text: String, relative: Option<f64>| -> String {
+        match (color, relative) {
+            (true, Some(r)) if r > 0.0 => format!("\x1b[31m{}\x1b[0m", text),
+            (true, Some(r)) if r < 0.0 => format!("\x1b[32m{}\x1b[0m", text),
+            _ => text,
+        }
+    };
+
+    let mut lines = vec![
+        format!(
+            "Comparing submission {} (base) -> {} (new)",
+            comparison.base_id, comparison.new_id
+        ),
+        String::new(),
+        format!(
+            "{:<28} {:<40} {:>12} {:>12} {:>12} {:>9} {:<6}",
+            "Run", "Benchmark", "Base", "New", "Delta", "Delta %", ""
+        ),
+        "-".repeat(125),
+    ];
+    for d in &comparison.benchmarks {
+        let row = format!(
+            "{:<28} {:<40} {:>12} {:>12} {:>12} {:>9} {:<6}",
+            truncate(&run_label(&d.mode, &d.runner, d.secret), 27),
+            truncate(&d.spec, 39),
+            format_mean(d.base_mean),
+            format_mean(d.new_mean),
+            format_delta(d.delta),
+            format_relative(d.relative_delta),
+            verdict(d.relative_delta),
+        );
+        lines.push(paint(row, d.relative_delta));
+    }
+
+    lines.push(String::new());
+    for g in &comparison.geomeans {
+        let row = format!(
+            "Geomean {} over {} shape(s): {} -> {} ({}, {})",
+            run_label(&g.mode, &g.runner, g.secret),
+            g.shapes,
+            format_mean(g.base_geomean),
+            format_mean(g.new_geomean),
+            format_relative(g.relative_delta),
+            verdict(g.relative_delta),
+        );
+        lines.push(paint(row, g.relative_delta));
+        if g.base_score.is_some() || g.new_score.is_some() {
+            lines.push(format!(
+                "  Score: {} -> {}",
+                format_score(g.base_score),
+                format_score(g.new_score)
+            ));
+        }
+    }
+
+    let regressions = comparison
+        .benchmarks
+        .iter()
+        .filter(|d| d.regression)
+        .count();
+    if regressions > 0 {
+        lines.push(String::new());
+        lines.push(paint(
+            format!("{} benchmark(s) regressed.", regressions),
+            Some(1.0),
+        ));
+    }
+
+    lines.join("\n")
+}
+
+fn render_markdown(comparison: &Comparison) -> String {
+    let mut lines = vec![
+        format!(
+            "### Submission {} vs {}",
+            comparison.base_id, comparison.new_id
+        ),
+        String::new(),
+        "| Run | Benchmark | Base | New | Delta | Delta % |".to_string(),
+        "| --- | --- | ---: | ---: | ---: | ---: |".to_string(),
+    ];
+    for d in &comparison.benchmarks {
+        let relative = if d.regression {
+            format!("**{}**", format_relative(d.relative_delta))
+        } else {
+            format_relative(d.relative_delta)
+        };
+        lines.push(format!(
+            "| {} | `{}` | {} | {} | {} | {} |",
+            run_label(&d.mode, &d.runner, d.secret),
+            d.spec,
+            format_mean(d.base_mean),
+            format_mean(d.new_mean),
+            format_delta(d.delta),
+            relative,
+        ));
+    }
+
+    lines.push(String::new());
+    lines.push(
+        "| Run | Shapes | Base geomean | New geomean | Change | Base score | New score |"
+            .to_string(),
+    );
+    lines.push("| --- | ---: | ---: | ---: | ---: | ---: | ---: |".to_string());
+    for g in &comparison.geomeans {
+        let relative = if g.regression {
+            format!("**{}**", format_relative(g.relative_delta))
+        } else {
+            format_relative(g.relative_delta)
+        };
+        lines.push(format!(
+            "| {} | {} | {} | {} | {} | {} | {} |",
+            run_label(&g.mode, &g.runner, g.secret),
+            g.shapes,
+            format_mean(g.base_geomean),
+            format_mean(g.new_geomean),
+            relative,
+            format_score(g.base_score),
+            format_score(g.new_score),
+        ));
+    }
+
+    lines.join("\n")
+}
+
+fn format_score(score: Option<f64>) -> String {
+    score
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn truncate(s: &str, max_len: usize) -> String {
+    if s.chars().count() <= max_len {
+        s.to_string()
+    } else {
+        let prefix: String = s.chars().take(max_len.saturating_sub(3)).collect();
+        format!("{}...", prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::fixtures::{self, details};
+    use crate::models::{BenchmarkResult, SubmissionRun};
+
+    fn run(mode: &str, secret: bool, score: Option<f64>, means: &[(&str, f64)]) -> SubmissionRun {
+        SubmissionRun {
+            benchmarks: means
+                .iter()
+                .map(|(spec, mean)| BenchmarkResult {
+                    spec: spec.to_string(),
+                    mean: Some(*mean),
+                    ..Default::default()
+                })
+                .collect(),
+            ..fixtures::run(mode, secret, score)
+        }
+    }
+
+    #[test]
+    fn test_compare_aligns_runs_by_mode_runner_and_spec() {
+        let base = details(
+            1,
+            vec![run(
+                "benchmark",
+                false,
+                None,
+                &[("m: 128", 1000.0), ("m: 256", 4000.0), ("m: 512", 9000.0)],
+            )],
+        );
+        let new = details(
+            2,
+            vec![run(
+                "benchmark",
+                false,
+                None,
+                &[("m: 256", 2000.0), ("m: 128", 1100.0), ("m: 1024", 5000.0)],
+            )],
+        );
+
+        let comparison = compare_submissions(&base, &new);
+
+        let specs: Vec<&str> = comparison
+            .benchmarks
+            .iter()
+            .map(|d| d.spec.as_str())
+            .collect();
+        assert_eq!(specs, vec!["m: 128", "m: 256", "m: 512", "m: 1024"]);
+
+        let slower = &comparison.benchmarks[0];
+        assert_eq!(slower.delta, Some(100.0));
+        assert!((slower.relative_delta.unwrap() - 0.1).abs() < 1e-12);
+        assert!(slower.regression);
+
+        let faster = &comparison.benchmarks[1];
+        assert_eq!(faster.relative_delta, Some(-0.5));
+        assert!(!faster.regression);
+
+        // Shapes only one side ran have no delta.
+        assert_eq!(comparison.benchmarks[2].new_mean, None);
+        assert_eq!(comparison.benchmarks[2].delta, None);
+
+        // The geomean only uses shapes both sides ran.
+        let geomean = &comparison.geomeans[0];
+        assert_eq!(geomean.shapes, 2);
+        assert!((geomean.base_geomean.unwrap() - 2000.0).abs() < 1e-9);
+        assert!(!geomean.regression);
+    }
+
+    #[test]
+    fn test_compare_keeps_secret_runs_separate() {
+        let base = details(
+            1,
+            vec![
+                run("leaderboard", false, Some(0.002), &[("m: 128", 1000.0)]),
+                run("leaderboard", true, Some(0.003), &[("m: 128", 1500.0)]),
+            ],
+        );
+        let new = details(
+            2,
+            vec![
+                run("leaderboard", false, Some(0.001), &[("m: 128", 900.0)]),
+                run("leaderboard", true, Some(0.004), &[("m: 128", 1800.0)]),
+            ],
+        );
+
+        let comparison = compare_submissions(&base, &new);
+
+        assert_eq!(comparison.benchmarks.len(), 2);
+        assert_eq!(comparison.geomeans.len(), 2);
+        assert_eq!(comparison.geomeans[0].base_score, Some(0.002));
+        assert_eq!(comparison.geomeans[1].new_score, Some(0.004));
+        assert!(!comparison.geomeans[0].regression);
+        assert!(comparison.geomeans[1].regression);
+    }
+
+    #[test]
+    fn test_render_markdown_bolds_regressions() {
+        let base = details(
+            1,
+            vec![run("benchmark", false, None, &[("m: 128", 1000.0)])],
+        );
+        let new = details(
+            2,
+            vec![run("benchmark", false, None, &[("m: 128", 1500.0)])],
+        );
+
+        let markdown = render_markdown(&compare_submissions(&base, &new));
+
+        assert!(markdown.contains(
+            "| benchmark on B200 | `m: 128` | 1.00 µs | 1.50 µs | +500 ns | **+50.00%** |"
+        ));
+    }
+
+    #[test]
+    fn test_render_table_without_color_has_no_escape_codes() {
+        let base = details(
+            1,
+            vec![run("benchmark", false, None, &[("m: 128", 1000.0)])],
+        );
+        let new = details(
+            2,
+            vec![run("benchmark", false, None, &[("m: 128", 1500.0)])],
+        );
+
+        let table = render_table(&compare_submissions(&base, &new), false);
+
+        assert!(!table.contains('\x1b'));
+        assert!(table.contains("slower"));
+        assert!(table.contains("1 benchmark(s) regressed."));
+    }
+}
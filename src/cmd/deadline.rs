@@ -0,0 +1,244 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use reqwest::Client;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::service;
+
+/// Warn when a leaderboard closes within this many hours, unless
+/// `--deadline-warn-hours` says otherwise.
+pub const DEFAULT_DEADLINE_WARN_HOURS: f64 = 24.0;
+
+/// Parse a competition deadline. Accepts RFC 3339 timestamps, naive
+/// `YYYY-MM-DD HH:MM` / `YYYY-MM-DDTHH:MM:SS` (UTC), and bare dates, which
+/// stay open until the end of that day.
+pub fn parse_deadline(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(text) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    for format in [
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S%.f",
+    ] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(text, format) {
+            return Some(dt.and_utc());
+        }
+    }
+    let date = NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?;
+    Some(date.succ_opt()?.and_hms_opt(0, 0, 0)?.and_utc())
+}
+
+/// "closes in 2d 3h", or "closed 5h ago" once past.
+pub fn format_countdown(deadline: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let remaining = deadline - now;
+    if remaining <= Duration::zero() {
+        return format!("closed {} ago", format_span(-remaining));
+    }
+    format!("closes in {}", format_span(remaining))
+}
+
+fn format_span(span: Duration) -> String {
+    let minutes = span.num_minutes();
+    match minutes {
+        m if m < 60 => format!("{}m", m.max(1)),
+        m if m < 24 * 60 => format!("{}h {}m", m / 60, m % 60),
+        m => format!("{}d {}h", m / (24 * 60), (m % (24 * 60)) / 60),
+    }
+}
+
+/// Refuse a closed leaderboard unless `force`; otherwise return a warning
+/// when it closes within `warn_hours`.
+fn check_deadline(
+    leaderboard: &str,
+    deadline: DateTime<Utc>,
+    now: DateTime<Utc>,
+    warn_hours: f64,
+    force: bool,
+) -> Result<Option<String>> {
+    let countdown = format_countdown(deadline, now);
+    if deadline <= now {
+        if !force {
+            return Err(anyhow!(
+                "Leaderboard {} {} ({}). Pass --force to submit anyway.",
+                leaderboard,
+                countdown,
+                deadline.format("%Y-%m-%d %H:%M UTC")
+            ));
+        }
+        return Ok(Some(format!(
+            "Leaderboard {} {}; submitting anyway because of --force.",
+            leaderboard, countdown
+        )));
+    }
+    let remaining_hours = (deadline - now).num_seconds() as f64 / 3600.0;
+    if remaining_hours < warn_hours {
+        return Ok(Some(format!(
+            "Warning: leaderboard {} {} ({}).",
+            leaderboard,
+            countdown,
+            deadline.format("%Y-%m-%d %H:%M UTC")
+        )));
+    }
+    Ok(None)
+}
+
+/// The leaderboard's deadline from the server, falling back to the
+/// competition index cached by `popcorn setup` when the server has none or
+/// cannot be reached. Fails only if neither source answers.
+pub async fn lookup_deadline(client: &Client, leaderboard: &str) -> Result<Option<DateTime<Utc>>> {
+    let leaderboards = match service::fetch_leaderboards(client).await {
+        Ok(leaderboards) => leaderboards,
+        Err(e) => return cached_deadline(leaderboard).map(Some).ok_or(e),
+    };
+    let from_server = leaderboards
+        .iter()
+        .find(|lb| lb.title_text.eq_ignore_ascii_case(leaderboard))
+        .and_then(|lb| lb.deadline.as_deref())
+        .and_then(parse_deadline);
+    if from_server.is_some() {
+        return Ok(from_server);
+    }
+    Ok(cached_deadline(leaderboard))
+}
+
+/// Look up the deadline and apply [`check_deadline`]. A failed lookup only
+/// warns; the server still has the final say.
+pub async fn check_before_submit(
+    client: &Client,
+    leaderboard: &str,
+    warn_hours: Option<f64>,
+    force: bool,
+    on_warning: &(dyn Fn(String) + Send + Sync),
+) -> Result<Option<DateTime<Utc>>> {
+    let deadline = match lookup_deadline(client, leaderboard).await {
+        Ok(Some(deadline)) => deadline,
+        Ok(None) => return Ok(None),
+        Err(e) => {
+            on_warning(format!(
+                "Warning: could not look up the deadline for {}: {}",
+                leaderboard, e
+            ));
+            return Ok(None);
+        }
+    };
+    let warn_hours = warn_hours.unwrap_or(DEFAULT_DEADLINE_WARN_HOURS);
+    if let Some(warning) = check_deadline(leaderboard, deadline, Utc::now(), warn_hours, force)? {
+        on_warning(warning);
+    }
+    Ok(Some(deadline))
+}
+
+fn cache_path() -> Result<PathBuf> {
+    dirs::home_dir()
+        .map(|mut path| {
+            path.push(".popcorn");
+            path.push("competitions.json");
+            path
+        })
+        .ok_or_else(|| anyhow!("Could not find home directory"))
+}
+
+/// Remember problem deadlines from the competition index
+/// (`~/.popcorn/competitions.json`), keyed by leaderboard name.
+pub fn cache_competition_deadlines(deadlines: &BTreeMap<String, String>) -> Result<()> {
+    write_cache(&cache_path()?, deadlines)
+}
+
+fn cached_deadline(leaderboard: &str) -> Option<DateTime<Utc>> {
+    let cache = read_cache(&cache_path().ok()?);
+    cache
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(leaderboard))
+        .and_then(|(_, deadline)| parse_deadline(deadline))
+}
+
+fn write_cache(path: &Path, deadlines: &BTreeMap<String, String>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| anyhow!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let mut cache = read_cache(path);
+    cache.extend(deadlines.clone());
+    fs::write(path, serde_json::to_string_pretty(&cache)?)
+        .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+}
+
+fn read_cache(path: &Path) -> BTreeMap<String, String> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        parse_deadline(text).unwrap()
+    }
+
+    #[test]
+    fn test_parse_deadline_formats() {
+        assert_eq!(
+            parse_deadline("2026-03-01T12:00:00+02:00"),
+            Some(utc("2026-03-01 10:00"))
+        );
+        assert_eq!(
+            parse_deadline("2026-03-01T10:00:00"),
+            Some(utc("2026-03-01 10:00"))
+        );
+        // A bare date stays open for the whole day.
+        assert_eq!(parse_deadline("2026-03-01"), Some(utc("2026-03-02 00:00")));
+        assert_eq!(parse_deadline(""), None);
+        assert_eq!(parse_deadline("soon"), None);
+    }
+
+    #[test]
+    fn test_check_deadline_warns_and_refuses() {
+        let now = utc("2026-03-01 10:00");
+
+        let far = utc("2026-03-05 10:00");
+        assert!(check_deadline("grayscale", far, now, 24.0, false)
+            .unwrap()
+            .is_none());
+
+        let soon = utc("2026-03-01 15:30");
+        let warning = check_deadline("grayscale", soon, now, 24.0, false)
+            .unwrap()
+            .unwrap();
+        assert!(warning.contains("closes in 5h 30m"));
+
+        let closed = utc("2026-02-28 22:00");
+        let err = check_deadline("grayscale", closed, now, 24.0, false).unwrap_err();
+        assert!(err.to_string().contains("closed 12h 0m ago"));
+        assert!(check_deadline("grayscale", closed, now, 24.0, true)
+            .unwrap()
+            .unwrap()
+            .contains("--force"));
+    }
+
+    #[test]
+    fn test_deadline_cache_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("popcorn").join("competitions.json");
+        let mut deadlines = BTreeMap::new();
+        deadlines.insert("grayscale".to_string(), "2026-03-01".to_string());
+        write_cache(&path, &deadlines).unwrap();
+        deadlines.clear();
+        deadlines.insert("vectoradd".to_string(), "2026-04-01 12:00".to_string());
+        write_cache(&path, &deadlines).unwrap();
+
+        let cache = read_cache(&path);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache["grayscale"], "2026-03-01");
+    }
+}
@@ -0,0 +1,105 @@
+use std::io::{self, Write};
+
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+use crate::service::{self, RemoteJob};
+
+/// Route Ctrl-C to a channel instead of killing the process, so a plain
+/// submit can offer to cancel the job it is waiting on.
+pub fn interrupts() -> Result<UnboundedReceiver<()>> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    ctrlc::set_handler(move || {
+        let _ = tx.send(());
+    })
+    .map_err(|e| anyhow!("Failed to install Ctrl-C handler: {}", e))?;
+    Ok(rx)
+}
+
+/// Handle Ctrl-C in plain mode: ask whether to cancel the remote jobs still
+/// running, report what happened, and return the error the command exits with.
+/// A second Ctrl-C at the prompt quits at once and leaves the jobs running.
+pub async fn cancel_on_interrupt(
+    client: &Client,
+    interrupts: &mut UnboundedReceiver<()>,
+) -> Result<()> {
+    let jobs = service::active_jobs();
+    if jobs.is_empty() {
+        return Err(anyhow!("Interrupted"));
+    }
+
+    eprint!("\nCancel remote job ({})? [y/N]: ", describe_jobs(&jobs));
+    io::stderr().flush()?;
+    let read_answer = tokio::task::spawn_blocking(|| {
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).map(|_| input)
+    });
+    let answer = tokio::select! {
+        answer = read_answer => answer??,
+        // The blocked read is abandoned; the process exits on the error.
+        Some(()) = interrupts.recv() => {
+            eprintln!();
+            String::new()
+        }
+    };
+
+    if answer.trim().eq_ignore_ascii_case("y") {
+        for line in cancel_jobs(client, &jobs).await {
+            eprintln!("{}", line);
+        }
+    } else {
+        for job in &jobs {
+            eprintln!("{}", leave_running_message(job));
+        }
+    }
+    Err(anyhow!("Interrupted"))
+}
+
+/// Cancel each job and describe whether the cancellation took effect.
+pub async fn cancel_jobs(client: &Client, jobs: &[RemoteJob]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for job in jobs {
+        lines.push(match service::cancel_remote_job(client, job).await {
+            Ok(outcome) if outcome.cancelled == Some(true) => format!("Cancelled {}.", job),
+            Ok(outcome) if outcome.cancelled.is_none() => with_check_later_hint(
+                format!(
+                    "Sent a cancel request for {}, but the server did not confirm it; the cancel status is unknown.",
+                    job
+                ),
+                job,
+            ),
+            Ok(outcome) => format!(
+                "Could not cancel {}: {}",
+                job,
+                outcome
+                    .message
+                    .unwrap_or_else(|| "the server declined".to_string())
+            ),
+            Err(e) => format!("Failed to cancel {}: {}", job, e),
+        });
+    }
+    lines
+}
+
+pub fn describe_jobs(jobs: &[RemoteJob]) -> String {
+    jobs.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn leave_running_message(job: &RemoteJob) -> String {
+    with_check_later_hint(format!("Left {} running.", job), job)
+}
+
+/// Append how to look the job up later, for jobs the CLI can show again.
+fn with_check_later_hint(message: String, job: &RemoteJob) -> String {
+    match job {
+        RemoteJob::Submission(id) => format!(
+            "{} Check it later with `popcorn submissions show {}`.",
+            message, id
+        ),
+        RemoteJob::BrevProfile { .. } => message,
+    }
+}
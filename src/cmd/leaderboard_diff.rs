@@ -0,0 +1,244 @@
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use similar::{ChangeTag, TextDiff};
+use std::io::{self, IsTerminal, Write};
+
+use crate::models::SubmissionDetails;
+use crate::service;
+use crate::utils;
+
+/// How many recent submissions with a run on the GPU are opened while looking
+/// for the latest leaderboard-mode one.
+const DIFF_LOOKBACK_CANDIDATES: usize = 10;
+
+/// How a leaderboard-mode submit is confirmed: `--yes` and
+/// `--confirm-diff-max-lines`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DiffConfirmation {
+    pub yes: bool,
+    pub max_lines: Option<usize>,
+}
+
+/// Changes between the user's latest leaderboard submission and the file
+/// about to replace it.
+#[derive(Debug)]
+pub struct SubmissionDiff {
+    pub previous_id: i64,
+    pub unified: String,
+    pub changed_lines: usize,
+}
+
+impl SubmissionDiff {
+    fn new(previous_id: i64, old: &str, new: &str, old_label: &str, new_label: &str) -> Self {
+        let diff = TextDiff::from_lines(old, new);
+        let changed_lines = diff
+            .iter_all_changes()
+            .filter(|change| change.tag() != ChangeTag::Equal)
+            .count();
+        let unified = diff
+            .unified_diff()
+            .context_radius(3)
+            .header(old_label, new_label)
+            .to_string();
+        Self {
+            previous_id,
+            unified,
+            changed_lines,
+        }
+    }
+
+    /// Why the change may go in without asking, if it is within `max_lines`.
+    pub fn auto_approval(&self, max_lines: Option<usize>) -> Option<String> {
+        let max_lines = max_lines.filter(|max| self.changed_lines <= *max)?;
+        Some(format!(
+            "{} changed line(s), within --confirm-diff-max-lines {}: submitting without confirmation.",
+            self.changed_lines, max_lines
+        ))
+    }
+
+    /// The diff with ANSI colours for added, removed and hunk lines.
+    fn colored(&self) -> String {
+        self.unified
+            .lines()
+            .map(|line| {
+                let color = if line.starts_with("+++") || line.starts_with("---") {
+                    "1"
+                } else if line.starts_with('+') {
+                    "32"
+                } else if line.starts_with('-') {
+                    "31"
+                } else if line.starts_with("@@") {
+                    "36"
+                } else {
+                    return line.to_string();
+                };
+                format!("\x1b[{}m{}\x1b[0m", color, line)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Diff `content` against the user's latest leaderboard submission on `gpu`.
+/// `None` when there is no earlier leaderboard submission or the file is not
+/// text (archives are not diffed).
+pub async fn diff_against_latest(
+    client: &Client,
+    filepath: &str,
+    content: &[u8],
+    leaderboard: &str,
+    gpu: &str,
+) -> Result<Option<SubmissionDiff>> {
+    if utils::is_archive_file(filepath) {
+        return Ok(None);
+    }
+    let Ok(new) = std::str::from_utf8(content) else {
+        return Ok(None);
+    };
+    let Some(previous) = latest_leaderboard_submission(client, leaderboard, gpu).await? else {
+        return Ok(None);
+    };
+
+    let old_label = format!("submission {} ({})", previous.id, previous.file_name);
+    Ok(Some(SubmissionDiff::new(
+        previous.id,
+        &previous.code,
+        new,
+        &old_label,
+        filepath,
+    )))
+}
+
+async fn latest_leaderboard_submission(
+    client: &Client,
+    leaderboard: &str,
+    gpu: &str,
+) -> Result<Option<SubmissionDetails>> {
+    let mut submissions = service::get_user_submissions(client, Some(leaderboard), None).await?;
+    submissions.sort_by_key(|sub| std::cmp::Reverse(sub.id));
+
+    let candidates = submissions
+        .iter()
+        .filter(|sub| {
+            sub.runs
+                .iter()
+                .any(|run| run.gpu_type.eq_ignore_ascii_case(gpu))
+        })
+        .take(DIFF_LOOKBACK_CANDIDATES);
+    for candidate in candidates {
+        let details = service::get_user_submission(client, candidate.id).await?;
+        if details
+            .runs
+            .iter()
+            .any(|run| run.mode == "leaderboard" && run.runner.eq_ignore_ascii_case(gpu))
+        {
+            return Ok(Some(details));
+        }
+    }
+    Ok(None)
+}
+
+/// Diff against the latest leaderboard submission and confirm on the
+/// terminal. Failing to fetch the previous submission only warns.
+pub async fn confirm_leaderboard_submit(
+    client: &Client,
+    filepath: &str,
+    content: &[u8],
+    leaderboard: &str,
+    gpu: &str,
+    confirmation: DiffConfirmation,
+) -> Result<()> {
+    match diff_against_latest(client, filepath, content, leaderboard, gpu).await {
+        Ok(Some(diff)) => confirm_plain(&diff, confirmation).await,
+        Ok(None) => Ok(()),
+        Err(e) => {
+            eprintln!(
+                "Warning: could not diff against your latest leaderboard submission: {}",
+                e
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Show the diff on stderr and ask before a leaderboard submit, unless
+/// `--yes` is set or the change is within `--confirm-diff-max-lines`.
+async fn confirm_plain(diff: &SubmissionDiff, confirmation: DiffConfirmation) -> Result<()> {
+    if diff.changed_lines == 0 {
+        eprintln!(
+            "No changes since leaderboard submission {}.",
+            diff.previous_id
+        );
+    } else {
+        eprintln!(
+            "\nChanges since your latest leaderboard submission {}:",
+            diff.previous_id
+        );
+        if io::stderr().is_terminal() {
+            eprintln!("{}", diff.colored());
+        } else {
+            eprintln!("{}", diff.unified.trim_end());
+        }
+    }
+
+    if let Some(reason) = diff.auto_approval(confirmation.max_lines) {
+        eprintln!("{}", reason);
+        return Ok(());
+    }
+    if confirmation.yes {
+        return Ok(());
+    }
+    if !io::stdin().is_terminal() {
+        return Err(anyhow!(
+            "Leaderboard submissions need confirmation. Pass --yes to submit without it."
+        ));
+    }
+
+    eprint!("Submit this to the leaderboard? [y/N]: ");
+    io::stderr().flush()?;
+    // Read off the runtime so the Ctrl-C handler racing this prompt can
+    // still fire while it waits for a line.
+    let answer = tokio::task::spawn_blocking(|| {
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).map(|_| input)
+    })
+    .await??;
+    if answer.trim().eq_ignore_ascii_case("y") {
+        Ok(())
+    } else {
+        Err(anyhow!("Submission cancelled."))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_submission_diff_counts_changed_lines() {
+        let diff = SubmissionDiff::new(
+            12,
+            "a\nb\nc\n",
+            "a\nB\nc\nd\n",
+            "submission 12 (old.py)",
+            "new.py",
+        );
+        assert_eq!(diff.changed_lines, 3);
+        assert!(diff
+            .unified
+            .starts_with("--- submission 12 (old.py)\n+++ new.py\n"));
+        assert!(diff.unified.contains("-b\n+B\n"));
+        assert!(diff.unified.contains("+d\n"));
+    }
+
+    #[test]
+    fn test_auto_approval_respects_max_lines() {
+        let diff = SubmissionDiff::new(1, "a\n", "b\n", "old", "new");
+        assert!(diff.auto_approval(None).is_none());
+        assert!(diff.auto_approval(Some(1)).is_none());
+        assert!(diff
+            .auto_approval(Some(2))
+            .unwrap()
+            .starts_with("2 changed line(s)"));
+    }
+}
@@ -0,0 +1,31 @@
+use anyhow::{anyhow, Result};
+use std::fs;
+
+use crate::history::runs;
+
+/// Print the saved stdout, stderr and result files of a submission's runs.
+pub fn show_logs(submission_id: &str) -> Result<()> {
+    let root = runs::runs_root();
+    let files = runs::saved_run_logs(&root, submission_id)?;
+    if files.is_empty() {
+        return Err(anyhow!(
+            "No saved logs for submission {} under {}",
+            submission_id,
+            root.display()
+        ));
+    }
+
+    for (i, file) in files.iter().enumerate() {
+        let content = fs::read_to_string(file)
+            .map_err(|e| anyhow!("Failed to read {}: {}", file.display(), e))?;
+        if i > 0 {
+            println!();
+        }
+        println!("==> {} <==", file.display());
+        print!("{}", content);
+        if !content.ends_with('\n') {
+            println!();
+        }
+    }
+    Ok(())
+}
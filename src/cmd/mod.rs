@@ -6,10 +6,21 @@ use std::path::PathBuf;
 
 mod admin;
 mod auth;
+mod auto_gpu;
+mod baseline;
+mod compare;
+mod deadline;
+mod interrupt;
+mod leaderboard_diff;
+mod logs;
+mod progress;
 mod setup;
 mod submissions;
 mod submit;
+mod test_matrix;
+mod watch;
 
+use crate::models::SubmissionMode;
 use crate::service;
 
 pub use admin::AdminAction;
@@ -58,9 +69,9 @@ pub struct Cli {
     #[arg(long)]
     pub leaderboard: Option<String>,
 
-    /// Optional: Specify submission mode (test, benchmark, leaderboard, profile)
-    #[arg(long)]
-    pub mode: Option<String>,
+    /// Optional: Specify submission mode; which modes are available depends on the leaderboard and GPU
+    #[arg(long, value_enum, ignore_case = true)]
+    pub mode: Option<SubmissionMode>,
 
     /// Profile on the GPU Mode Brev B200 and save the Nsight Compute trace locally
     #[arg(long)]
@@ -115,6 +126,8 @@ enum SubmissionsAction {
         #[arg(long)]
         force: bool,
     },
+    /// Review submissions flagged as hacked, from the local record
+    Flagged,
 }
 
 #[derive(Subcommand, Debug)]
@@ -133,17 +146,21 @@ enum Commands {
         /// Optional: Path to the solution file (can also be provided as a top-level argument)
         filepath: Option<String>,
 
-        /// Optional: Directly specify the GPU to use (e.g., "MI300")
+        /// Optional: Directly specify the GPU to use (e.g., "MI300"), or `auto` for the least loaded one
         #[arg(long)]
         gpu: Option<String>,
 
+        /// With --gpu auto: GPUs to favour when queues are tied (comma-separated)
+        #[arg(long, value_delimiter = ',', value_name = "GPUS", requires = "gpu")]
+        prefer: Vec<String>,
+
         /// Optional: Directly specify the leaderboard (e.g., "amd-fp8-mm")
         #[arg(long)]
         leaderboard: Option<String>,
 
-        /// Optional: Specify submission mode (test, benchmark, leaderboard, profile)
-        #[arg(long)]
-        mode: Option<String>,
+        /// Optional: Specify submission mode; which modes are available depends on the leaderboard and GPU
+        #[arg(long, value_enum, ignore_case = true)]
+        mode: Option<SubmissionMode>,
 
         /// Profile on the GPU Mode Brev B200 and save the Nsight Compute trace locally
         #[arg(long)]
@@ -160,6 +177,63 @@ enum Commands {
         /// Skip the TUI and print results directly to stdout
         #[arg(long)]
         no_tui: bool,
+
+        /// Submit even if identical code already ran in the same mode on the same GPU
+        #[arg(long)]
+        allow_duplicate: bool,
+
+        /// Benchmark mode only: compare against a submission id, a geomean score in seconds, or `history-best`
+        #[arg(long)]
+        baseline: Option<baseline::Baseline>,
+
+        /// Exit non-zero if the geomean is more than this percent slower than --baseline
+        #[arg(long, value_name = "PERCENT", requires = "baseline")]
+        fail_if_slower_than: Option<f64>,
+
+        /// Benchmark mode only: resubmit in leaderboard mode if the geomean beats your best leaderboard score
+        #[arg(long)]
+        promote_if_better: bool,
+
+        /// Resubmit in test mode whenever the file or its local imports change
+        #[arg(
+            long,
+            conflicts_with_all = ["profile_brev", "baseline", "promote_if_better", "output"]
+        )]
+        watch: bool,
+
+        /// Hold the submission locally until fewer than N jobs are queued on the runner
+        #[arg(
+            long,
+            value_name = "N",
+            conflicts_with = "profile_brev",
+            value_parser = clap::value_parser!(i64).range(1..)
+        )]
+        max_queue: Option<i64>,
+
+        /// Leaderboard mode: submit without confirming the diff against your latest leaderboard submission
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Leaderboard mode: skip the confirmation when at most N lines changed
+        #[arg(long, value_name = "N")]
+        confirm_diff_max_lines: Option<usize>,
+
+        /// Submit even if the leaderboard's deadline has passed
+        #[arg(long)]
+        force: bool,
+
+        /// Warn when the leaderboard closes within this many hours (default 24)
+        #[arg(long, value_name = "HOURS")]
+        deadline_warn_hours: Option<f64>,
+
+        /// Write progress events to stdout in this format instead of text (implies --no-tui)
+        #[arg(
+            long,
+            value_enum,
+            value_name = "FORMAT",
+            conflicts_with_all = ["watch", "promote_if_better"]
+        )]
+        events: Option<progress::EventFormat>,
     },
     /// Join a closed leaderboard using an invite code
     Join {
@@ -176,6 +250,32 @@ enum Commands {
         #[command(subcommand)]
         action: SubmissionsAction,
     },
+    /// Compare per-benchmark timings of two of your submissions
+    Compare {
+        /// Baseline submission ID
+        base_id: i64,
+
+        /// Submission ID to compare against the baseline
+        new_id: i64,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = compare::CompareFormat::Table)]
+        format: compare::CompareFormat,
+    },
+    /// Run tests on every GPU of a leaderboard and show a pass/fail matrix
+    TestMatrix {
+        /// Path to the solution file
+        filepath: String,
+
+        /// Optional: Directly specify the leaderboard (e.g., "amd-fp8-mm")
+        #[arg(long)]
+        leaderboard: Option<String>,
+    },
+    /// Show the stdout, stderr and result JSON saved for a submission's runs
+    Logs {
+        /// Submission ID (or Brev job ID for profiles)
+        id: String,
+    },
 }
 
 pub async fn execute(cli: Cli) -> Result<()> {
@@ -198,12 +298,24 @@ pub async fn execute(cli: Cli) -> Result<()> {
         Some(Commands::Submit {
             filepath,
             gpu,
+            prefer,
             leaderboard,
             mode,
             profile_brev,
             benchmark_index,
             output,
             no_tui,
+            allow_duplicate,
+            baseline,
+            fail_if_slower_than,
+            promote_if_better,
+            watch,
+            max_queue,
+            yes,
+            confirm_diff_max_lines,
+            force,
+            deadline_warn_hours,
+            events,
         }) => {
             let config = load_config()?;
             let cli_id = config.cli_id.ok_or_else(|| {
@@ -221,33 +333,54 @@ pub async fn execute(cli: Cli) -> Result<()> {
             } else {
                 gpu
             };
+            if !prefer.is_empty() && !final_gpu.as_deref().is_some_and(auto_gpu::is_auto) {
+                return Err(anyhow!("--prefer only applies with --gpu auto"));
+            }
             let final_mode = if profile_brev {
-                Some("profile".to_string())
+                Some(SubmissionMode::Profile)
+            } else if watch {
+                match mode {
+                    Some(mode) if mode != SubmissionMode::Test => {
+                        return Err(anyhow!("--watch only supports --mode test"));
+                    }
+                    _ => Some(SubmissionMode::Test),
+                }
             } else {
                 mode
             };
 
-            if no_tui || profile_brev {
-                submit::run_submit_plain(
-                    final_filepath, // Resolved filepath
-                    final_gpu,      // From Submit command
-                    leaderboard,    // From Submit command
-                    final_mode,     // From Submit command
-                    cli_id,
-                    benchmark_index.or(cli.benchmark_index),
-                    output, // From Submit command
-                )
-                .await
+            let options = submit::SubmitOptions {
+                filepath: final_filepath,
+                gpu: final_gpu,
+                leaderboard,
+                mode: final_mode,
+                benchmark_index: benchmark_index.or(cli.benchmark_index),
+                output,
+                allow_duplicate,
+                baseline,
+                fail_if_slower_than,
+                promote_if_better,
+                watch,
+                max_queue,
+                prefer,
+                yes,
+                confirm_diff_max_lines,
+                force,
+                deadline_warn_hours,
+                events,
+            };
+
+            // The regression gate and auto-promotion chain several runs, and
+            // event output needs stdout, so they always use plain output.
+            if no_tui
+                || profile_brev
+                || options.baseline.is_some()
+                || options.promote_if_better
+                || options.events.is_some()
+            {
+                submit::run_submit_plain(options, cli_id).await
             } else {
-                submit::run_submit_tui(
-                    final_filepath, // Resolved filepath
-                    final_gpu,      // From Submit command
-                    leaderboard,    // From Submit command
-                    final_mode,     // From Submit command
-                    cli_id,
-                    output, // From Submit command
-                )
-                .await
+                submit::run_submit_tui(options, cli_id).await
             }
         }
         Some(Commands::Join { code }) => {
@@ -275,27 +408,63 @@ pub async fn execute(cli: Cli) -> Result<()> {
         }
         Some(Commands::Admin { action }) => admin::handle_admin(action).await,
         Some(Commands::Submissions { action }) => {
-            let config = load_config()?;
-            let cli_id = config.cli_id.ok_or_else(|| {
-                anyhow!(
-                    "cli_id not found in config file ({}). Please run `popcorn register` first.",
-                    get_config_path()
-                        .map_or_else(|_| "unknown path".to_string(), |p| p.display().to_string())
-                )
-            })?;
+            // Only the server-backed actions need a registered CLI.
+            let cli_id = || {
+                let config = load_config()?;
+                config.cli_id.ok_or_else(|| {
+                    anyhow!(
+                        "cli_id not found in config file ({}). Please run `popcorn register` first.",
+                        get_config_path().map_or_else(
+                            |_| "unknown path".to_string(),
+                            |p| p.display().to_string()
+                        )
+                    )
+                })
+            };
 
             match action {
                 SubmissionsAction::List { leaderboard, limit } => {
-                    submissions::list_submissions(cli_id, leaderboard, Some(limit)).await
+                    submissions::list_submissions(cli_id()?, leaderboard, Some(limit)).await
                 }
                 SubmissionsAction::Show { id, no_code } => {
-                    submissions::show_submission(cli_id, id, no_code).await
+                    submissions::show_submission(cli_id()?, id, no_code).await
                 }
                 SubmissionsAction::Delete { id, force } => {
-                    submissions::delete_submission(cli_id, id, force).await
+                    submissions::delete_submission(cli_id()?, id, force).await
                 }
+                SubmissionsAction::Flagged => submissions::list_flagged(),
             }
         }
+        Some(Commands::Compare {
+            base_id,
+            new_id,
+            format,
+        }) => {
+            let config = load_config()?;
+            let cli_id = config.cli_id.ok_or_else(|| {
+                anyhow!(
+                    "cli_id not found in config file ({}). Please run `popcorn register` first.",
+                    get_config_path()
+                        .map_or_else(|_| "unknown path".to_string(), |p| p.display().to_string())
+                )
+            })?;
+            compare::run_compare(cli_id, base_id, new_id, format).await
+        }
+        Some(Commands::TestMatrix {
+            filepath,
+            leaderboard,
+        }) => {
+            let config = load_config()?;
+            let cli_id = config.cli_id.ok_or_else(|| {
+                anyhow!(
+                    "cli_id not found in config file ({}). Please run `popcorn register` first.",
+                    get_config_path()
+                        .map_or_else(|_| "unknown path".to_string(), |p| p.display().to_string())
+                )
+            })?;
+            test_matrix::run_test_matrix(cli_id, filepath, leaderboard).await
+        }
+        Some(Commands::Logs { id }) => logs::show_logs(&id),
         None => {
             // Check if any of the submission-related flags were used at the top level
             if !cli.profile_brev
@@ -320,24 +489,26 @@ pub async fn execute(cli: Cli) -> Result<()> {
 
                 if cli.profile_brev {
                     submit::run_submit_plain(
-                        Some(top_level_filepath),
-                        Some("B200_Brev".to_string()),
-                        cli.leaderboard,
-                        Some("profile".to_string()),
+                        submit::SubmitOptions {
+                            filepath: Some(top_level_filepath),
+                            gpu: Some("B200_Brev".to_string()),
+                            leaderboard: cli.leaderboard,
+                            mode: Some(SubmissionMode::Profile),
+                            benchmark_index: cli.benchmark_index,
+                            output: cli.output,
+                            ..Default::default()
+                        },
                         cli_id,
-                        cli.benchmark_index,
-                        cli.output,
                     )
                     .await
                 } else {
                     // Run TUI with only filepath, no other options
                     submit::run_submit_tui(
-                        Some(top_level_filepath),
-                        None, // No GPU option
-                        None, // No leaderboard option
-                        None, // No mode option
+                        submit::SubmitOptions {
+                            filepath: Some(top_level_filepath),
+                            ..Default::default()
+                        },
                         cli_id,
-                        None, // No output option
                     )
                     .await
                 }
@@ -0,0 +1,237 @@
+use std::path::PathBuf;
+use std::time::Instant;
+
+use anyhow::Result;
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::ValueEnum;
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::service::{EventSender, SubmissionEvent};
+
+/// Machine-readable progress output for `popcorn submit --events`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum EventFormat {
+    /// One JSON object per line on stdout
+    Ndjson,
+}
+
+/// What the closing `result` line of `--events ndjson` reports.
+#[derive(Debug, Default)]
+pub struct FinalResult {
+    pub submission_id: Option<String>,
+    pub result: Option<Value>,
+}
+
+/// What a submission's events said about it, for output after the result.
+#[derive(Debug, Default)]
+pub struct ProgressSummary {
+    /// The accepted submission's id, if there was one.
+    pub submission_id: Option<String>,
+    /// Where the run logs were saved, if they were.
+    pub logs_dir: Option<PathBuf>,
+}
+
+/// Feed a submission's events to `handle` as they arrive. Returns the sender
+/// to hand to the service and a task that ends once every sender is dropped,
+/// so awaiting it flushes progress before the result is printed.
+fn spawn_consumer<F>(mut handle: F) -> (EventSender, JoinHandle<ProgressSummary>)
+where
+    F: FnMut(&SubmissionEvent, Option<&str>) + Send + 'static,
+{
+    let (sender, mut receiver) = mpsc::unbounded_channel::<SubmissionEvent>();
+    let task = tokio::spawn(async move {
+        let mut summary = ProgressSummary::default();
+        while let Some(event) = receiver.recv().await {
+            match &event {
+                SubmissionEvent::Accepted { id } => summary.submission_id = Some(id.clone()),
+                SubmissionEvent::LogsSaved { dir, .. } => summary.logs_dir = Some(dir.clone()),
+                _ => {}
+            }
+            handle(&event, summary.submission_id.as_deref());
+        }
+        summary
+    });
+    (sender, task)
+}
+
+/// Render a submission's events as text with `render`; see `spawn_consumer`.
+pub fn spawn_renderer<F>(mut render: F) -> (EventSender, JoinHandle<ProgressSummary>)
+where
+    F: FnMut(String) + Send + 'static,
+{
+    let started = Instant::now();
+    spawn_consumer(move |event, _| {
+        if let Some(text) = event.progress_text(started.elapsed().as_secs()) {
+            render(text);
+        }
+    })
+}
+
+/// Print a submission's events to stderr, each prefixed with `prefix`.
+pub fn print_to_stderr(prefix: &str) -> (EventSender, JoinHandle<ProgressSummary>) {
+    let prefix = prefix.to_string();
+    spawn_renderer(move |text| eprintln!("{}{}", prefix, text))
+}
+
+/// Write a submission's events to stdout as NDJSON.
+pub fn write_ndjson() -> (EventSender, JoinHandle<ProgressSummary>) {
+    spawn_consumer(|event, submission_id| {
+        if let Some(line) = event_json(event, submission_id, Utc::now()) {
+            println!("{}", line);
+        }
+    })
+}
+
+/// The NDJSON object for an event, or `None` for `Finished`, which the
+/// closing `result` line reports instead.
+fn event_json(
+    event: &SubmissionEvent,
+    submission_id: Option<&str>,
+    timestamp: DateTime<Utc>,
+) -> Option<Value> {
+    let mut object = match event {
+        SubmissionEvent::Accepted { .. } => json!({ "type": "accepted" }),
+        SubmissionEvent::Queued {
+            runner,
+            position,
+            eta_seconds,
+        } => json!({
+            "type": "queue",
+            "runner": runner,
+            "position": position,
+            "eta_seconds": eta_seconds,
+        }),
+        SubmissionEvent::StatusChanged { status } => json!({ "type": "status", "status": status }),
+        SubmissionEvent::Stdout { run, text } => {
+            json!({ "type": "stdout", "run": run, "text": text })
+        }
+        SubmissionEvent::Stderr { run, text } => {
+            json!({ "type": "stderr", "run": run, "text": text })
+        }
+        SubmissionEvent::BenchmarkResult { run, results } => json!({
+            "type": "run_result",
+            "run": run,
+            "tests": results.tests,
+            "benchmarks": results.benchmarks,
+        }),
+        SubmissionEvent::ProfileReport {
+            profiler,
+            spec,
+            report,
+        } => json!({
+            "type": "profile_report",
+            "profiler": profiler,
+            "spec": spec,
+            "report": report,
+        }),
+        SubmissionEvent::ProfileArtifact { path, url } => json!({
+            "type": "artifact",
+            "path": path.as_ref().map(|p| p.display().to_string()),
+            "url": url,
+        }),
+        SubmissionEvent::DownloadProgress {
+            name,
+            downloaded,
+            total,
+        } => json!({
+            "type": "download",
+            "name": name,
+            "downloaded": downloaded,
+            "total": total,
+        }),
+        SubmissionEvent::LogsSaved { dir, files } => json!({
+            "type": "logs",
+            "dir": dir.display().to_string(),
+            "files": files.iter().map(|f| f.display().to_string()).collect::<Vec<_>>(),
+        }),
+        SubmissionEvent::Notice(message) => json!({ "type": "notice", "message": message }),
+        SubmissionEvent::Failed { error } => json!({ "type": "error", "error": error }),
+        SubmissionEvent::Finished => return None,
+    };
+    stamp(&mut object, submission_id, timestamp);
+    Some(object)
+}
+
+/// The closing NDJSON line: whether the command succeeded, and either the
+/// final result or the error.
+pub fn result_json(
+    final_result: &FinalResult,
+    outcome: &Result<()>,
+    timestamp: DateTime<Utc>,
+) -> Value {
+    let mut object = match outcome {
+        Ok(()) => json!({
+            "type": "result",
+            "ok": true,
+            "result": final_result.result,
+        }),
+        Err(e) => json!({
+            "type": "result",
+            "ok": false,
+            "error": e.to_string(),
+        }),
+    };
+    stamp(
+        &mut object,
+        final_result.submission_id.as_deref(),
+        timestamp,
+    );
+    object
+}
+
+fn stamp(object: &mut Value, submission_id: Option<&str>, timestamp: DateTime<Utc>) {
+    object["timestamp"] = json!(timestamp.to_rfc3339_opts(SecondsFormat::Millis, true));
+    object["submission_id"] = json!(submission_id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_event_json_carries_type_timestamp_and_id() {
+        let at = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        let queued = SubmissionEvent::Queued {
+            runner: Some("Modal B200".to_string()),
+            position: 2,
+            eta_seconds: None,
+        };
+        assert_eq!(
+            event_json(&queued, Some("42"), at).unwrap(),
+            json!({
+                "type": "queue",
+                "runner": "Modal B200",
+                "position": 2,
+                "eta_seconds": null,
+                "timestamp": "2026-03-01T12:00:00.000Z",
+                "submission_id": "42",
+            })
+        );
+        assert_eq!(event_json(&SubmissionEvent::Finished, Some("42"), at), None);
+        let failed = SubmissionEvent::Failed {
+            error: "boom".to_string(),
+        };
+        assert_eq!(event_json(&failed, None, at).unwrap()["type"], "error");
+    }
+
+    #[test]
+    fn test_result_json_reports_outcome() {
+        let at = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        let final_result = FinalResult {
+            submission_id: Some("42".to_string()),
+            result: Some(json!({ "id": 42 })),
+        };
+        let ok = result_json(&final_result, &Ok(()), at);
+        assert_eq!(ok["ok"], true);
+        assert_eq!(ok["result"]["id"], 42);
+        assert_eq!(ok["submission_id"], "42");
+
+        let failed = result_json(&FinalResult::default(), &Err(anyhow::anyhow!("closed")), at);
+        assert_eq!(failed["ok"], false);
+        assert_eq!(failed["error"], "closed");
+        assert_eq!(failed["submission_id"], Value::Null);
+    }
+}
@@ -1,12 +1,15 @@
 use anyhow::{anyhow, Context, Result};
-use chrono::{NaiveDate, NaiveDateTime, Utc};
+use chrono::Utc;
 use serde::Deserialize;
 use serde_json::json;
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 
+use crate::cmd::deadline;
+
 const SKILL_NAME: &str = "popcorn-submission-workflow";
 const NATIVE_SKILL_NAME: &str = "load-inline-native-code";
 const SUBMISSION_FILENAME: &str = "submission.py";
@@ -31,18 +34,8 @@ struct CompetitionIndex {
 }
 
 fn is_active(deadline: &str) -> bool {
-    let deadline = deadline.trim();
-    if deadline.is_empty() {
-        return true; // No deadline means always open
-    }
-    let now = Utc::now().naive_utc();
-    if let Ok(dt) = NaiveDateTime::parse_from_str(deadline, "%Y-%m-%d %H:%M") {
-        return now < dt;
-    }
-    if let Ok(d) = NaiveDate::parse_from_str(deadline, "%Y-%m-%d") {
-        return now.date() <= d;
-    }
-    true // Can't parse — show it rather than hide it
+    // No deadline means always open; one we can't parse is shown rather than hidden.
+    deadline::parse_deadline(deadline).is_none_or(|dt| Utc::now() < dt)
 }
 
 #[derive(Deserialize)]
@@ -112,6 +105,7 @@ async fn discover_competition_yamls(client: &reqwest::Client) -> Result<Vec<Stri
 async fn fetch_competition_index(client: &reqwest::Client) -> Result<Vec<(String, ProblemEntry)>> {
     let yaml_files = discover_competition_yamls(client).await?;
     let mut entries = Vec::new();
+    let mut deadlines = BTreeMap::new();
     for filename in &yaml_files {
         let url = format!("{}/{}", RAW_GITHUB_BASE, filename);
         let resp = client
@@ -130,6 +124,11 @@ async fn fetch_competition_index(client: &reqwest::Client) -> Result<Vec<(String
         let text = resp.text().await?;
         let index: CompetitionIndex =
             serde_yaml::from_str(&text).with_context(|| format!("Failed to parse {}", filename))?;
+        if !index.deadline.trim().is_empty() {
+            for problem in &index.problems {
+                deadlines.insert(problem.name.clone(), index.deadline.clone());
+            }
+        }
         if !is_active(&index.deadline) {
             continue;
         }
@@ -138,6 +137,10 @@ async fn fetch_competition_index(client: &reqwest::Client) -> Result<Vec<(String
             entries.push((comp_name.clone(), problem));
         }
     }
+    // `submit` falls back to these when the server does not report a deadline.
+    if let Err(e) = deadline::cache_competition_deadlines(&deadlines) {
+        eprintln!("Warning: could not cache competition deadlines: {}", e);
+    }
     if entries.is_empty() {
         return Err(anyhow!(
             "No competitions found. Check your network connection."
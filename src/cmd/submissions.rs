@@ -1,6 +1,7 @@
 use anyhow::Result;
 use std::io::{self, Write};
 
+use crate::history::flagged;
 use crate::service;
 
 /// List user's submissions for a leaderboard
@@ -173,6 +174,41 @@ fn format_score(score: Option<f64>) -> String {
         .unwrap_or_else(|| "-".to_string())
 }
 
+/// List the locally recorded `hacked` verdicts, newest first, with the
+/// explanation shown when each came in.
+pub fn list_flagged() -> Result<()> {
+    let verdicts = flagged::load_flagged()?;
+    if verdicts.is_empty() {
+        println!("No flagged submissions recorded.");
+        return Ok(());
+    }
+
+    for (i, verdict) in verdicts.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!(
+            "Submission {} ({}, {} on {}) flagged at {}",
+            verdict.submission_id,
+            verdict.file_name,
+            verdict.leaderboard,
+            verdict.gpu,
+            verdict.flagged_at
+        );
+        println!(
+            "  {}",
+            verdict
+                .error
+                .as_deref()
+                .unwrap_or("No error details were provided")
+        );
+        if !verdict.explanation.is_empty() {
+            println!("{}", verdict.explanation);
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,23 +1,71 @@
 use std::fs::File;
 use std::io::{self, Read};
 use std::path::Path;
+use std::pin::pin;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen};
+use futures_util::future;
+use futures_util::stream::{self, StreamExt};
 use ratatui::prelude::*;
 use ratatui::style::{Color, Style, Stylize};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, List, ListItem, ListState};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
 use serde_json::Value;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
 use tokio::task::JoinHandle;
 
-use crate::models::{AppState, GpuItem, LeaderboardItem, SubmissionModeItem};
-use crate::service;
+use crate::cmd::auto_gpu;
+use crate::cmd::baseline::{self, Baseline};
+use crate::cmd::deadline;
+use crate::cmd::interrupt;
+use crate::cmd::leaderboard_diff::{self, DiffConfirmation, SubmissionDiff};
+use crate::cmd::progress::{self, EventFormat, FinalResult};
+use crate::cmd::watch::{self, FileWatcher, StatusLine};
+use crate::history;
+use crate::models::{
+    AppState, GpuItem, LeaderboardItem, SubmissionMode, SubmissionModeItem, SupportedModes,
+};
+use crate::service::{self, RemoteJob, SubmissionEvent};
 use crate::utils;
 use crate::views::loading_page::{LoadingPage, LoadingPageState};
 use crate::views::result_page::{ResultPage, ResultPageState};
 
+/// How many of the user's most recent server submissions are checked for
+/// byte-identical code before a new submission is uploaded.
+const DUPLICATE_LOOKBACK_SUBMISSIONS: i32 = 20;
+/// Server submissions fetched at once while looking for a duplicate.
+const DUPLICATE_LOOKUP_CONCURRENCY: usize = 5;
+/// Lines PageUp/PageDown scroll the waiting screen's log by.
+const LOG_PAGE_LINES: u16 = 10;
+
+/// Options shared by the TUI and plain submit front ends.
+#[derive(Default, Debug, Clone)]
+pub struct SubmitOptions {
+    pub filepath: Option<String>,
+    pub gpu: Option<String>,
+    pub leaderboard: Option<String>,
+    pub mode: Option<SubmissionMode>,
+    pub benchmark_index: Option<usize>,
+    pub output: Option<String>,
+    pub allow_duplicate: bool,
+    pub baseline: Option<Baseline>,
+    pub fail_if_slower_than: Option<f64>,
+    pub promote_if_better: bool,
+    pub watch: bool,
+    pub max_queue: Option<i64>,
+    pub prefer: Vec<String>,
+    pub yes: bool,
+    pub confirm_diff_max_lines: Option<usize>,
+    pub force: bool,
+    pub deadline_warn_hours: Option<f64>,
+    pub events: Option<EventFormat>,
+}
+
 #[derive(Default, Debug)]
 pub struct App {
     pub filepath: String,
@@ -33,7 +81,42 @@ pub struct App {
 
     pub submission_modes: Vec<SubmissionModeItem>,
     pub submission_modes_state: ListState,
-    pub selected_submission_mode: Option<String>,
+    pub selected_submission_mode: Option<SubmissionMode>,
+    /// Leaderboard/GPU pair the mode list was last fetched for.
+    pub modes_loaded_for: Option<(String, String)>,
+    pub modes_task: Option<JoinHandle<Result<Option<SupportedModes>, anyhow::Error>>>,
+
+    pub allow_duplicate: bool,
+    pub max_queue: Option<i64>,
+    pub prefer: Vec<String>,
+    pub force: bool,
+    pub deadline_warn_hours: Option<f64>,
+
+    /// Leaderboard deadline, once the submission task has looked it up.
+    pub deadline: Arc<Mutex<Option<DateTime<Utc>>>>,
+
+    /// Note shown under the waiting screen's header, e.g. the `--watch` state.
+    pub status_message: Option<String>,
+    /// Progress events of the running submission, and when it started.
+    pub events: Option<UnboundedReceiver<SubmissionEvent>>,
+    pub submission_started: Option<Instant>,
+
+    /// Set with `--watch`: resubmit in test mode whenever the sources change.
+    pub watcher: Option<FileWatcher>,
+    pub watch_run: u32,
+    /// Remote jobs of a superseded `--watch` run, cancelled by the next one.
+    pub superseded_jobs: Vec<RemoteJob>,
+
+    /// Leaderboard-mode confirmation: the diff against the latest
+    /// leaderboard submission, fetched before anything is uploaded.
+    pub confirmation: DiffConfirmation,
+    pub leaderboard_diff: Option<SubmissionDiff>,
+    pub diff_scroll: u16,
+    pub diff_task: Option<JoinHandle<Result<Option<SubmissionDiff>, anyhow::Error>>>,
+
+    /// Remote jobs the user is being asked to cancel after pressing q.
+    pub cancel_prompt: Option<Vec<RemoteJob>>,
+    pub cancel_task: Option<JoinHandle<Vec<String>>>,
 
     pub app_state: AppState,
     pub final_status: Option<String>,
@@ -50,28 +133,10 @@ pub struct App {
 
 impl App {
     pub fn new<P: AsRef<Path>>(filepath: P, cli_id: String) -> Self {
-        let submission_modes = vec![
-            SubmissionModeItem::new(
-                "Test".to_string(),
-                "Test the solution and give detailed results about passed/failed tests.".to_string(),
-                "test".to_string(),
-            ),
-            SubmissionModeItem::new(
-                "Benchmark".to_string(),
-                "Benchmark the solution, this also runs the tests and afterwards runs the benchmark, returning detailed timing results".to_string(),
-                "benchmark".to_string(),
-            ),
-            SubmissionModeItem::new(
-                "Leaderboard".to_string(),
-                "Submit to the leaderboard, this first runs public tests and then private tests. If both pass, the submission is evaluated and submit to the leaderboard.".to_string(),
-                "leaderboard".to_string(),
-            ),
-            SubmissionModeItem::new(
-                "Profile".to_string(),
-                "Profile the solution using Nsight Compute (NVIDIA) or rocPROF (AMD). Downloads profiling data to current directory.".to_string(),
-                "profile".to_string(),
-            ),
-        ];
+        let submission_modes = SubmissionMode::ALL
+            .into_iter()
+            .map(|mode| SubmissionModeItem::for_mode(mode, &[]))
+            .collect();
 
         let mut app = Self {
             filepath: filepath.as_ref().to_string_lossy().to_string(),
@@ -87,21 +152,41 @@ impl App {
         app
     }
 
-    pub fn update_loading_page_state(&mut self, terminal_width: u16) {
+    pub fn update_loading_page_state(&mut self) {
         if self.app_state != AppState::WaitingForResult {
             return;
         }
 
         let st = &mut self.loading_page_state;
-        st.progress_column = {
-            if st.progress_column < terminal_width {
-                st.progress_column + 1
+        if let Some(started) = self.submission_started {
+            st.elapsed_secs = started.elapsed().as_secs();
+        }
+        st.deadline_line = self
+            .deadline
+            .lock()
+            .ok()
+            .and_then(|deadline| *deadline)
+            .map(|deadline| {
+                format!(
+                    "{} {}",
+                    self.selected_leaderboard
+                        .as_deref()
+                        .unwrap_or("Leaderboard"),
+                    deadline::format_countdown(deadline, Utc::now())
+                )
+            });
+        if let Some(jobs) = &self.cancel_prompt {
+            st.status_line = Some(if self.cancel_task.is_some() {
+                format!("Cancelling {}...", interrupt::describe_jobs(jobs))
             } else {
-                st.loop_count += 1;
-                0
-            }
-        };
-        st.progress_bar = f64::from(st.progress_column) * 100.0 / f64::from(terminal_width);
+                format!(
+                    "Cancel remote job ({})? [y/N]",
+                    interrupt::describe_jobs(jobs)
+                )
+            });
+        } else {
+            st.status_line = self.status_message.clone();
+        }
     }
 
     pub fn initialize_with_directives(&mut self, popcorn_directives: utils::PopcornDirectives) {
@@ -128,17 +213,27 @@ impl App {
     }
 
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Result<bool> {
+        if self.cancel_prompt.is_some() {
+            return self.handle_cancel_prompt_key(key);
+        }
+        if self.app_state == AppState::ConfirmLeaderboardDiff {
+            return Ok(self.handle_diff_confirm_key(key));
+        }
+
         // Allow quitting anytime, even while loading
-        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
-            self.should_quit = true;
+        let ctrl_c =
+            key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl_c || key.code == KeyCode::Char('q') {
+            let jobs = service::active_jobs();
+            if self.app_state == AppState::WaitingForResult && !jobs.is_empty() {
+                self.cancel_prompt = Some(jobs);
+            } else {
+                self.should_quit = true;
+            }
             return Ok(true);
         }
 
         match key.code {
-            KeyCode::Char('q') => {
-                self.should_quit = true;
-                return Ok(true);
-            }
             KeyCode::Enter => match self.app_state {
                 AppState::LeaderboardSelection => {
                     if let Some(idx) = self.leaderboards_state.selected() {
@@ -171,12 +266,14 @@ impl App {
                     }
                 }
                 AppState::SubmissionModeSelection => {
+                    // Wait for the server's mode list before accepting a choice.
+                    if self.modes_task.is_some() {
+                        return Ok(false);
+                    }
                     if let Some(idx) = self.submission_modes_state.selected() {
                         if idx < self.submission_modes.len() {
-                            self.selected_submission_mode =
-                                Some(self.submission_modes[idx].value.clone());
-                            self.app_state = AppState::WaitingForResult;
-                            if let Err(e) = self.spawn_submit_solution() {
+                            self.selected_submission_mode = Some(self.submission_modes[idx].value);
+                            if let Err(e) = self.start_submission() {
                                 self.set_error_and_quit(format!(
                                     "Error starting submission: {}",
                                     e
@@ -196,11 +293,80 @@ impl App {
                 self.move_selection_down();
                 return Ok(true);
             }
+            KeyCode::PageUp if self.app_state == AppState::WaitingForResult => {
+                self.loading_page_state.scroll_up(LOG_PAGE_LINES);
+                return Ok(true);
+            }
+            KeyCode::PageDown if self.app_state == AppState::WaitingForResult => {
+                self.loading_page_state.scroll_down(LOG_PAGE_LINES);
+                return Ok(true);
+            }
+            KeyCode::End if self.app_state == AppState::WaitingForResult => {
+                self.loading_page_state.log_scroll = 0;
+                return Ok(true);
+            }
             _ => {}
         }
         Ok(false)
     }
 
+    /// y submits, arrows scroll the diff, anything else cancels.
+    fn handle_diff_confirm_key(&mut self, key: KeyEvent) -> bool {
+        if self.diff_task.is_some() {
+            // Still fetching; only allow backing out.
+            if key.code == KeyCode::Char('q') || key.code == KeyCode::Esc {
+                self.should_quit = true;
+                return true;
+            }
+            return false;
+        }
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => self.submit_now(),
+            KeyCode::Up => self.diff_scroll = self.diff_scroll.saturating_sub(1),
+            KeyCode::Down => self.diff_scroll = self.diff_scroll.saturating_add(1),
+            KeyCode::PageUp => self.diff_scroll = self.diff_scroll.saturating_sub(10),
+            KeyCode::PageDown => self.diff_scroll = self.diff_scroll.saturating_add(10),
+            _ => self.should_quit = true,
+        }
+        true
+    }
+
+    fn handle_cancel_prompt_key(&mut self, key: KeyEvent) -> Result<bool> {
+        if self.cancel_task.is_some() {
+            return Ok(false);
+        }
+        let Some(jobs) = self.cancel_prompt.clone() else {
+            return Ok(false);
+        };
+
+        if key.code == KeyCode::Char('y') || key.code == KeyCode::Char('Y') {
+            let client = service::create_client(Some(self.cli_id.clone()))?;
+            self.cancel_task = Some(tokio::spawn(async move {
+                interrupt::cancel_jobs(&client, &jobs).await
+            }));
+        } else {
+            let lines: Vec<String> = jobs.iter().map(interrupt::leave_running_message).collect();
+            self.final_status = Some(lines.join("\n"));
+            self.should_quit = true;
+        }
+        Ok(true)
+    }
+
+    pub async fn check_cancel_task(&mut self) {
+        if let Some(handle) = &mut self.cancel_task {
+            if handle.is_finished() {
+                let task = self.cancel_task.take().unwrap();
+                match task.await {
+                    Ok(lines) => {
+                        self.final_status = Some(lines.join("\n"));
+                        self.should_quit = true;
+                    }
+                    Err(e) => self.set_error_and_quit(format!("Task join error: {}", e)),
+                }
+            }
+        }
+    }
+
     fn set_error_and_quit(&mut self, error_message: String) {
         self.final_status = Some(error_message);
         self.should_quit = true;
@@ -229,6 +395,7 @@ impl App {
                     }
                 }
             }
+            AppState::WaitingForResult => self.loading_page_state.scroll_up(1),
             _ => {}
         }
     }
@@ -256,6 +423,7 @@ impl App {
                     }
                 }
             }
+            AppState::WaitingForResult => self.loading_page_state.scroll_down(1),
             _ => {}
         }
     }
@@ -280,6 +448,65 @@ impl App {
         Ok(())
     }
 
+    /// Leaderboard submissions first show the diff against the latest
+    /// leaderboard submission; everything else is submitted straight away.
+    pub fn start_submission(&mut self) -> Result<()> {
+        let leaderboard_mode = self.selected_submission_mode == Some(SubmissionMode::Leaderboard);
+        let gpu = self.selected_gpu.clone().unwrap_or_default();
+        if !leaderboard_mode || self.confirmation.yes {
+            self.app_state = AppState::WaitingForResult;
+            return self.spawn_submit_solution();
+        }
+
+        let client = service::create_client(Some(self.cli_id.clone()))?;
+        let filepath = self.filepath.clone();
+        let leaderboard = self
+            .selected_leaderboard
+            .clone()
+            .ok_or_else(|| anyhow!("Leaderboard not selected"))?;
+        let file_content = std::fs::read(&filepath)?;
+        self.app_state = AppState::ConfirmLeaderboardDiff;
+        self.diff_task = Some(tokio::spawn(async move {
+            leaderboard_diff::diff_against_latest(
+                &client,
+                &filepath,
+                &file_content,
+                &leaderboard,
+                &gpu,
+            )
+            .await
+        }));
+        Ok(())
+    }
+
+    fn submit_now(&mut self) {
+        self.app_state = AppState::WaitingForResult;
+        if let Err(e) = self.spawn_submit_solution() {
+            self.set_error_and_quit(format!("Error starting submission: {}", e));
+        }
+    }
+
+    pub async fn check_diff_task(&mut self) {
+        if let Some(handle) = &mut self.diff_task {
+            if handle.is_finished() {
+                let task = self.diff_task.take().unwrap();
+                match task.await {
+                    Ok(Ok(Some(diff))) => {
+                        if diff.auto_approval(self.confirmation.max_lines).is_some() {
+                            self.submit_now();
+                        } else {
+                            self.leaderboard_diff = Some(diff);
+                        }
+                    }
+                    // Nothing to compare with, or the lookup failed: the
+                    // plain path only warns in that case too.
+                    Ok(Ok(None)) | Ok(Err(_)) => self.submit_now(),
+                    Err(e) => self.set_error_and_quit(format!("Task join error: {}", e)),
+                }
+            }
+        }
+    }
+
     pub fn spawn_submit_solution(&mut self) -> Result<()> {
         let client = service::create_client(Some(self.cli_id.clone()))?;
         let filepath = self.filepath.clone();
@@ -293,7 +520,6 @@ impl App {
             .ok_or_else(|| anyhow!("GPU not selected"))?;
         let mode = self
             .selected_submission_mode
-            .clone()
             .ok_or_else(|| anyhow!("Submission mode not selected"))?;
 
         // Read file content as bytes (supports both text and archive files)
@@ -301,21 +527,201 @@ impl App {
         let mut file_content = Vec::new();
         file.read_to_end(&mut file_content)?;
 
+        let title = if self.watcher.is_some() {
+            self.watch_run += 1;
+            self.status_message = Some("Watching for changes; q stops.".to_string());
+            format!(
+                "Run {}: {} on {} ({})",
+                self.watch_run, mode, leaderboard, gpu
+            )
+        } else {
+            format!("{} on {} ({})", mode, leaderboard, gpu)
+        };
+        self.loading_page_state.start_run(&title);
+        let (events, receiver) = mpsc::unbounded_channel();
+        self.events = Some(receiver);
+        self.submission_started = Some(Instant::now());
+
+        // Watch mode resubmits on every save, and an edit that is later
+        // reverted is legitimately identical to an earlier run.
+        let check_duplicate = !self.allow_duplicate && self.watcher.is_none();
+        let max_queue = self.max_queue;
+        let prefer = self.prefer.clone();
+        let force = self.force;
+        let deadline_warn_hours = self.deadline_warn_hours;
+        let deadline_slot = Arc::clone(&self.deadline);
+        let superseded = std::mem::take(&mut self.superseded_jobs);
         self.submission_task = Some(tokio::spawn(async move {
+            for line in interrupt::cancel_jobs(&client, &superseded).await {
+                let _ = events.send(SubmissionEvent::Notice(format!("Superseded run: {}", line)));
+            }
+            let deadline = deadline::check_before_submit(
+                &client,
+                &leaderboard,
+                deadline_warn_hours,
+                force,
+                &|msg| {
+                    let _ = events.send(SubmissionEvent::Notice(msg));
+                },
+            )
+            .await?;
+            if let Ok(mut slot) = deadline_slot.lock() {
+                *slot = deadline;
+            }
+            let (gpu, auto_reason) = if auto_gpu::is_auto(&gpu) {
+                let choice = auto_gpu::choose_gpu(&client, &leaderboard, &prefer).await?;
+                let _ = events.send(SubmissionEvent::Notice(choice.reason.clone()));
+                (choice.gpu, Some(choice.reason))
+            } else {
+                (gpu, None)
+            };
+            ensure_mode_supported(&client, &leaderboard, &gpu, mode).await?;
+            if check_duplicate {
+                // Lookup failures are not fatal; warn in the log like the
+                // plain path does.
+                match find_duplicate_submission(&client, &file_content, &leaderboard, &gpu, mode)
+                    .await
+                {
+                    Ok(Some(duplicate)) => return Err(anyhow!(duplicate.refusal_message())),
+                    Ok(None) => {}
+                    Err(e) => {
+                        let _ = events.send(SubmissionEvent::Notice(format!(
+                            "Warning: could not check for duplicate submissions: {}",
+                            e
+                        )));
+                    }
+                }
+            }
+            if let Some(max_queue) = max_queue {
+                service::wait_for_queue_below(
+                    &client,
+                    &leaderboard,
+                    &gpu,
+                    max_queue,
+                    Some(&events),
+                )
+                .await?;
+            }
             service::submit_solution(
                 &client,
                 &filepath,
                 &file_content,
                 &leaderboard,
                 &gpu,
-                &mode,
-                None,
+                mode,
+                Some(events),
             )
             .await
+            .map(|result| match auto_reason {
+                Some(reason) => format!("{}\n\n{}", reason, result),
+                None => result,
+            })
         }));
         Ok(())
     }
 
+    /// Feed the running submission's progress events to the waiting screen.
+    pub fn check_events(&mut self) {
+        let Some(events) = self.events.as_mut() else {
+            return;
+        };
+        let elapsed = self
+            .submission_started
+            .map_or(0, |started| started.elapsed().as_secs());
+        while let Ok(event) = events.try_recv() {
+            self.loading_page_state.record_event(&event, elapsed);
+        }
+    }
+
+    /// Drive `--watch`: skip the mode picker (watch always tests) and replace
+    /// the in-flight run once a change to the sources has settled.
+    pub fn check_watch(&mut self) {
+        let Some(watcher) = self.watcher.as_mut() else {
+            return;
+        };
+        match self.app_state {
+            AppState::SubmissionModeSelection => {
+                self.selected_submission_mode = Some(SubmissionMode::Test);
+                self.app_state = AppState::WaitingForResult;
+            }
+            AppState::WaitingForResult => {
+                if !watcher.poll() {
+                    return;
+                }
+                if let Some(task) = self.submission_task.take() {
+                    // Aborting only stops the local wait; the next run
+                    // cancels the job left on the runner queue.
+                    self.superseded_jobs = service::active_jobs();
+                    task.abort();
+                }
+            }
+            _ => return,
+        }
+        if let Err(e) = self.spawn_submit_solution() {
+            self.set_error_and_quit(format!("Error starting submission: {}", e));
+        }
+    }
+
+    /// Fetch the modes the selected leaderboard/GPU supports once the mode
+    /// picker is shown.
+    pub fn check_modes(&mut self) {
+        if self.app_state != AppState::SubmissionModeSelection || self.modes_task.is_some() {
+            return;
+        }
+        let (Some(leaderboard), Some(gpu)) = (&self.selected_leaderboard, &self.selected_gpu)
+        else {
+            return;
+        };
+        let target = (leaderboard.clone(), gpu.clone());
+        if self.modes_loaded_for.as_ref() == Some(&target) {
+            return;
+        }
+        if auto_gpu::is_auto(gpu) {
+            self.modes_loaded_for = Some(target);
+            self.submission_modes = vec![SubmissionModeItem::for_mode(SubmissionMode::Test, &[])];
+            self.submission_modes_state.select(Some(0));
+            return;
+        }
+        let client = match service::create_client(Some(self.cli_id.clone())) {
+            Ok(client) => client,
+            Err(e) => return self.set_error_and_quit(format!("Error starting mode fetch: {}", e)),
+        };
+        let (leaderboard, gpu) = target.clone();
+        self.modes_loaded_for = Some(target);
+        self.modes_task = Some(tokio::spawn(async move {
+            service::fetch_supported_modes(&client, &leaderboard, &gpu).await
+        }));
+    }
+
+    pub async fn check_modes_task(&mut self) {
+        if let Some(handle) = &mut self.modes_task {
+            if handle.is_finished() {
+                let task = self.modes_task.take().unwrap();
+                match task.await {
+                    Ok(Ok(Some(supported))) => {
+                        if supported.modes.is_empty() {
+                            return self.set_error_and_quit(format!(
+                                "No submission modes are available for {} on {}",
+                                self.selected_leaderboard.as_deref().unwrap_or("N/A"),
+                                self.selected_gpu.as_deref().unwrap_or("N/A")
+                            ));
+                        }
+                        self.submission_modes = SubmissionMode::ALL
+                            .into_iter()
+                            .filter(|mode| supported.modes.contains(mode))
+                            .map(|mode| SubmissionModeItem::for_mode(mode, &supported.profilers))
+                            .collect();
+                        self.submission_modes_state.select(Some(0));
+                    }
+                    // An older server, or a failed lookup: offer every mode
+                    // and let the server reject what it does not support.
+                    Ok(Ok(None)) | Ok(Err(_)) => {}
+                    Err(e) => self.set_error_and_quit(format!("Task join error: {}", e)),
+                }
+            }
+        }
+    }
+
     pub async fn check_leaderboard_task(&mut self) {
         if let Some(handle) = &mut self.leaderboards_task {
             if handle.is_finished() {
@@ -394,7 +800,30 @@ impl App {
         if let Some(handle) = &mut self.submission_task {
             if handle.is_finished() {
                 let task = self.submission_task.take().unwrap();
-                match task.await {
+                let result = task.await;
+                if self.watcher.is_some() {
+                    // Keep watching; the last result is shown when the user quits.
+                    let outcome = match result {
+                        Ok(Ok(status)) => {
+                            self.final_status = Some(status);
+                            "finished"
+                        }
+                        Ok(Err(e)) => {
+                            self.final_status = Some(format!("Submission error: {}", e));
+                            "failed"
+                        }
+                        Err(e) => {
+                            return self.set_error_and_quit(format!("Task join error: {}", e))
+                        }
+                    };
+                    self.submission_started = None;
+                    self.status_message = Some(format!(
+                        "Run {} {}. Waiting for changes (q shows the last result)...",
+                        self.watch_run, outcome
+                    ));
+                    return;
+                }
+                match result {
                     Ok(Ok(status)) => {
                         self.final_status = Some(status);
                         self.should_quit = true; // Quit after showing final status
@@ -501,9 +930,14 @@ pub fn ui(app: &App, frame: &mut Frame) {
                 .collect::<Vec<ListItem>>();
             let list = List::new(items)
                 .block(list_block.title(format!(
-                    "Select Submission Mode for '{}' on '{}'",
+                    "Select Submission Mode for '{}' on '{}'{}",
                     app.selected_leaderboard.as_deref().unwrap_or("N/A"),
-                    app.selected_gpu.as_deref().unwrap_or("N/A")
+                    app.selected_gpu.as_deref().unwrap_or("N/A"),
+                    if app.modes_task.is_some() {
+                        " (checking available modes...)"
+                    } else {
+                        ""
+                    }
                 )))
                 .style(list_style)
                 .highlight_style(Style::default().bg(Color::DarkGray))
@@ -514,6 +948,38 @@ pub fn ui(app: &App, frame: &mut Frame) {
                 &mut app.submission_modes_state.clone(),
             );
         }
+        AppState::ConfirmLeaderboardDiff => {
+            let (title, lines) = match &app.leaderboard_diff {
+                None => (
+                    "Leaderboard submission".to_string(),
+                    vec![Line::from(
+                        "Fetching your latest leaderboard submission to diff against...",
+                    )],
+                ),
+                Some(diff) if diff.changed_lines == 0 => (
+                    "Submit to the leaderboard? [y/N]".to_string(),
+                    vec![Line::from(format!(
+                        "No changes since leaderboard submission {}.",
+                        diff.previous_id
+                    ))],
+                ),
+                Some(diff) => (
+                    format!(
+                        "{} changed line(s) since leaderboard submission {}. Submit? [y/N] (arrows scroll)",
+                        diff.changed_lines, diff.previous_id
+                    ),
+                    diff.unified
+                        .lines()
+                        .map(|line| Line::styled(line.to_string(), diff_line_style(line)))
+                        .collect(),
+                ),
+            };
+            let paragraph = Paragraph::new(lines)
+                .block(list_block.title(title))
+                .style(list_style)
+                .scroll((app.diff_scroll, 0));
+            frame.render_widget(paragraph, main_layout[0]);
+        }
         AppState::WaitingForResult => {
             let loading_page = LoadingPage::default();
             frame.render_stateful_widget(
@@ -525,14 +991,38 @@ pub fn ui(app: &App, frame: &mut Frame) {
     }
 }
 
-pub async fn run_submit_tui(
-    filepath: Option<String>,
-    gpu: Option<String>,
-    leaderboard: Option<String>,
-    mode: Option<String>,
-    cli_id: String,
-    output: Option<String>,
-) -> Result<()> {
+fn diff_line_style(line: &str) -> Style {
+    if line.starts_with("+++") || line.starts_with("---") {
+        Style::default().bold()
+    } else if line.starts_with('+') {
+        Style::default().fg(Color::Green)
+    } else if line.starts_with('-') {
+        Style::default().fg(Color::Red)
+    } else if line.starts_with("@@") {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default()
+    }
+}
+
+pub async fn run_submit_tui(options: SubmitOptions, cli_id: String) -> Result<()> {
+    let SubmitOptions {
+        filepath,
+        gpu,
+        leaderboard,
+        mode,
+        output,
+        allow_duplicate,
+        watch,
+        max_queue,
+        prefer,
+        yes,
+        confirm_diff_max_lines,
+        force,
+        deadline_warn_hours,
+        ..
+    } = options;
+
     let file_to_submit = match filepath {
         Some(fp) => fp,
         None => {
@@ -557,6 +1047,18 @@ pub async fn run_submit_tui(
     }
 
     let mut app = App::new(&file_to_submit, cli_id);
+    app.allow_duplicate = allow_duplicate;
+    app.max_queue = max_queue;
+    app.prefer = prefer;
+    app.force = force;
+    app.deadline_warn_hours = deadline_warn_hours;
+    app.confirmation = DiffConfirmation {
+        yes,
+        max_lines: confirm_diff_max_lines,
+    };
+    if watch {
+        app.watcher = Some(FileWatcher::new(&file_to_submit));
+    }
 
     // Override directives with CLI flags if provided
     if let Some(gpu_flag) = gpu {
@@ -566,6 +1068,9 @@ pub async fn run_submit_tui(
         app.selected_leaderboard = Some(leaderboard_flag);
     }
     if let Some(mode_flag) = mode {
+        if app.selected_gpu.as_deref().is_some_and(auto_gpu::is_auto) {
+            auto_gpu::ensure_test_mode(mode_flag)?;
+        }
         app.selected_submission_mode = Some(mode_flag);
         // Skip to submission if we have all required fields
         if app.selected_gpu.is_some() && app.selected_leaderboard.is_some() {
@@ -592,7 +1097,7 @@ pub async fn run_submit_tui(
             }
         }
         AppState::WaitingForResult => {
-            if let Err(e) = app.spawn_submit_solution() {
+            if let Err(e) = app.start_submission() {
                 return Err(anyhow!("Error starting submission: {}", e));
             }
         }
@@ -611,9 +1116,15 @@ pub async fn run_submit_tui(
 
         app.check_leaderboard_task().await;
         app.check_gpu_task().await;
+        app.check_modes();
+        app.check_modes_task().await;
+        app.check_diff_task().await;
+        app.check_events();
         app.check_submission_task().await;
+        app.check_cancel_task().await;
+        app.check_watch();
 
-        app.update_loading_page_state(terminal.size()?.width);
+        app.update_loading_page_state();
 
         if event::poll(std::time::Duration::from_millis(50))? {
             if let Event::Key(key) = event::read()? {
@@ -650,6 +1161,12 @@ pub async fn run_submit_tui(
             .map_err(|e| anyhow!("Failed to write result to file {}: {}", output_path, e))?;
     }
 
+    // The log pane is gone once the result shows, so repeat where the run
+    // logs went (but keep them out of the --output file).
+    if let Some(dir) = &app.loading_page_state.logs_dir {
+        result_text.push_str(&format!("\n\n{}", logs_saved_note(dir)));
+    }
+
     let state = &mut app.result_page_state;
 
     let mut result_page = ResultPage::new(result_text.clone(), state);
@@ -681,15 +1198,57 @@ pub async fn run_submit_tui(
     Ok(())
 }
 
-pub async fn run_submit_plain(
-    filepath: Option<String>,
-    gpu: Option<String>,
-    leaderboard: Option<String>,
-    mode: Option<String>,
-    cli_id: String,
-    benchmark_index: Option<usize>,
-    output: Option<String>,
+pub async fn run_submit_plain(options: SubmitOptions, cli_id: String) -> Result<()> {
+    let client = service::create_client(Some(cli_id))?;
+    let mut interrupts = interrupt::interrupts()?;
+    let events = options.events;
+    let mut final_result = FinalResult::default();
+
+    let outcome = {
+        // Keep the submission future alive while handling Ctrl-C so the job
+        // it is waiting on stays registered for cancellation.
+        let submission = submit_plain(options, &client, &mut final_result);
+        tokio::pin!(submission);
+        tokio::select! {
+            result = &mut submission => result,
+            Some(()) = interrupts.recv() => interrupt::cancel_on_interrupt(&client, &mut interrupts).await,
+        }
+    };
+    if events == Some(EventFormat::Ndjson) {
+        println!(
+            "{}",
+            progress::result_json(&final_result, &outcome, Utc::now())
+        );
+    }
+    outcome
+}
+
+async fn submit_plain(
+    options: SubmitOptions,
+    client: &reqwest::Client,
+    final_result: &mut FinalResult,
 ) -> Result<()> {
+    let SubmitOptions {
+        filepath,
+        gpu,
+        leaderboard,
+        mode,
+        benchmark_index,
+        output,
+        allow_duplicate,
+        baseline,
+        fail_if_slower_than,
+        promote_if_better,
+        watch,
+        max_queue,
+        prefer,
+        yes,
+        confirm_diff_max_lines,
+        force,
+        deadline_warn_hours,
+        events: event_format,
+    } = options;
+
     let file_to_submit = match filepath {
         Some(fp) => fp,
         None => {
@@ -741,42 +1300,159 @@ pub async fn run_submit_plain(
     let mut file_content = Vec::new();
     file.read_to_end(&mut file_content)?;
 
-    eprintln!("Submitting to leaderboard: {}", final_leaderboard);
-    eprintln!("GPU: {}", final_gpu);
-    eprintln!("Mode: {}", final_mode);
-    eprintln!("File: {}", file_to_submit);
-    eprintln!("\nWaiting for results...");
+    let final_gpu = if auto_gpu::is_auto(&final_gpu) {
+        auto_gpu::ensure_test_mode(final_mode)?;
+        let choice = auto_gpu::choose_gpu(client, &final_leaderboard, &prefer).await?;
+        eprintln!("{}", choice.reason);
+        choice.gpu
+    } else {
+        final_gpu
+    };
 
-    // Create client and submit
-    let client = service::create_client(Some(cli_id))?;
-    let result = if final_mode.eq_ignore_ascii_case("profile")
-        && final_gpu.eq_ignore_ascii_case("B200_Brev")
-    {
-        service::profile_brev_solution(
-            &client,
-            &file_to_submit,
+    if baseline.is_some() && final_mode != SubmissionMode::Benchmark {
+        return Err(anyhow!("--baseline requires --mode benchmark"));
+    }
+    if promote_if_better && final_mode != SubmissionMode::Benchmark {
+        return Err(anyhow!("--promote-if-better requires --mode benchmark"));
+    }
+
+    if !final_gpu.eq_ignore_ascii_case("B200_Brev") {
+        ensure_mode_supported(client, &final_leaderboard, &final_gpu, final_mode).await?;
+    }
+
+    deadline::check_before_submit(
+        client,
+        &final_leaderboard,
+        deadline_warn_hours,
+        force,
+        &|msg| eprintln!("{}", msg),
+    )
+    .await?;
+
+    if watch {
+        return run_watch_plain(
+            client.clone(),
+            file_to_submit,
+            final_leaderboard,
+            final_gpu,
+            max_queue,
+        )
+        .await;
+    }
+
+    // Resolve the baseline up front so a bad id fails before using GPU time.
+    let resolved_baseline = match &baseline {
+        Some(baseline) => Some(
+            baseline::resolve_baseline(client, baseline, &final_leaderboard, &final_gpu).await?,
+        ),
+        None => None,
+    };
+
+    if !allow_duplicate {
+        match find_duplicate_submission(
+            client,
             &file_content,
             &final_leaderboard,
-            benchmark_index,
-            Some(Box::new(|msg| {
-                eprintln!("{}", msg);
-            })),
+            &final_gpu,
+            final_mode,
         )
-        .await?
-    } else {
-        service::submit_solution(
-            &client,
+        .await
+        {
+            Ok(Some(duplicate)) => return Err(anyhow!(duplicate.refusal_message())),
+            Ok(None) => {}
+            Err(e) => eprintln!("Warning: could not check for duplicate submissions: {}", e),
+        }
+    }
+
+    let confirmation = DiffConfirmation {
+        yes,
+        max_lines: confirm_diff_max_lines,
+    };
+    if final_mode == SubmissionMode::Leaderboard {
+        leaderboard_diff::confirm_leaderboard_submit(
+            client,
             &file_to_submit,
             &file_content,
             &final_leaderboard,
             &final_gpu,
-            &final_mode,
-            Some(Box::new(|msg| {
-                eprintln!("{}", msg);
-            })),
+            confirmation,
         )
-        .await?
+        .await?;
+    }
+
+    let (events, printer) = match event_format {
+        Some(EventFormat::Ndjson) => progress::write_ndjson(),
+        None => progress::print_to_stderr(""),
     };
+    if let Some(max_queue) = max_queue {
+        if !final_gpu.eq_ignore_ascii_case("B200_Brev") {
+            service::wait_for_queue_below(
+                client,
+                &final_leaderboard,
+                &final_gpu,
+                max_queue,
+                Some(&events),
+            )
+            .await?;
+        }
+    }
+
+    eprintln!("Submitting to leaderboard: {}", final_leaderboard);
+    eprintln!("GPU: {}", final_gpu);
+    eprintln!("Mode: {}", final_mode);
+    eprintln!("File: {}", file_to_submit);
+    eprintln!("\nWaiting for results...");
+
+    let outcome =
+        if final_mode == SubmissionMode::Profile && final_gpu.eq_ignore_ascii_case("B200_Brev") {
+            service::profile_brev_solution(
+                client,
+                &file_to_submit,
+                &file_content,
+                &final_leaderboard,
+                benchmark_index,
+                Some(events),
+            )
+            .await
+            .map(|text| (text, None))
+        } else if final_mode == SubmissionMode::Profile {
+            service::submit_solution(
+                client,
+                &file_to_submit,
+                &file_content,
+                &final_leaderboard,
+                &final_gpu,
+                final_mode,
+                Some(events),
+            )
+            .await
+            .map(|text| (text, None))
+        } else {
+            match service::submit_solution_background(
+                client,
+                &file_to_submit,
+                &file_content,
+                &final_leaderboard,
+                &final_gpu,
+                final_mode,
+                Some(events),
+            )
+            .await
+            {
+                Ok(finished) => {
+                    service::format_submission_details(&finished).map(|text| (text, Some(finished)))
+                }
+                Err(e) => Err(e),
+            }
+        };
+    // Let the progress output finish before the result is printed.
+    let progress = printer.await.unwrap_or_default();
+    final_result.submission_id = progress.submission_id.clone();
+    let (result, details) = outcome?;
+    final_result.result = Some(match &details {
+        Some(details) => serde_json::to_value(details)?,
+        None => serde_json::from_str(&result).unwrap_or_else(|_| Value::String(result.clone())),
+    });
 
     // Clean up the result text
     let trimmed = result.trim();
@@ -801,13 +1477,438 @@ pub async fn run_submit_plain(
         eprintln!("\nResults written to: {}", output_path);
     }
 
-    // Print to stdout
-    println!("\n{}", content);
-    print_profile_report_links(profile_report_links);
+    // Print to stdout, unless it carries NDJSON events
+    if event_format.is_none() {
+        println!("\n{}", content);
+        print_profile_report_links(profile_report_links);
+        // The progress log has scrolled past, so repeat where the run logs
+        // went.
+        if let Some(dir) = &progress.logs_dir {
+            println!("\n{}", logs_saved_note(dir));
+        }
+    }
+
+    if let (Some(resolved), Some(details)) = (&resolved_baseline, &details) {
+        let run = baseline::benchmark_run(details, &final_gpu).ok_or_else(|| {
+            anyhow!(
+                "Submission {} has no benchmark run on {}",
+                details.id,
+                final_gpu
+            )
+        })?;
+        let (report, regressed) =
+            baseline::regression_report(resolved, &run.benchmarks, fail_if_slower_than)?;
+        eprintln!("\n{}", report);
+        if let Some(threshold) = fail_if_slower_than {
+            if regressed {
+                return Err(anyhow!(
+                    "Submission {} is more than {}% slower than {}",
+                    details.id,
+                    threshold,
+                    resolved.label
+                ));
+            }
+            eprintln!("Regression check passed.");
+        }
+    }
+
+    if let (true, Some(details)) = (promote_if_better, &details) {
+        promote_to_leaderboard(
+            client,
+            details,
+            &file_to_submit,
+            &file_content,
+            &final_leaderboard,
+            &final_gpu,
+            LeaderboardGuards {
+                allow_duplicate,
+                confirmation,
+            },
+        )
+        .await?;
+    }
 
     Ok(())
 }
 
+/// Where a run's logs went and how to show them again. The directory is
+/// named after the submission, or the local run or Brev job id for runs
+/// without one, and that name is what `popcorn logs` takes.
+fn logs_saved_note(dir: &Path) -> String {
+    let name = dir.file_name().map_or_else(
+        || dir.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    format!(
+        "Run logs saved to {} (popcorn logs {} shows them again)",
+        dir.display(),
+        name
+    )
+}
+
+/// `--watch` without the TUI: resubmit in test mode whenever the submission or
+/// its local includes change, superseding the run still in flight, until the
+/// user presses Ctrl-C. There is no duplicate check: reverting an edit
+/// legitimately resubmits code that already ran.
+async fn run_watch_plain(
+    client: reqwest::Client,
+    file_to_submit: String,
+    leaderboard: String,
+    gpu: String,
+    max_queue: Option<i64>,
+) -> Result<()> {
+    let mut watcher = FileWatcher::new(&file_to_submit);
+    eprintln!(
+        "Watching for changes on {} ({}), Ctrl-C to stop:",
+        leaderboard, gpu
+    );
+    for file in watcher.files() {
+        eprintln!("  {}", file.display());
+    }
+
+    let status_line = Arc::new(StatusLine::on_stderr());
+    let mut run = 0;
+    loop {
+        run += 1;
+        status_line.set(&format!("[watch] run {}: submitting...", run));
+
+        let (events, renderer) = {
+            let status_line = Arc::clone(&status_line);
+            progress::spawn_renderer(move |msg| {
+                status_line.set(&format!("[watch] run {}: {}", run, msg))
+            })
+        };
+        let task = {
+            let client = client.clone();
+            let file_to_submit = file_to_submit.clone();
+            let leaderboard = leaderboard.clone();
+            let gpu = gpu.clone();
+            tokio::spawn(async move {
+                let file_content = std::fs::read(&file_to_submit)
+                    .map_err(|e| anyhow!("Failed to read {}: {}", file_to_submit, e))?;
+                if let Some(max_queue) = max_queue {
+                    service::wait_for_queue_below(
+                        &client,
+                        &leaderboard,
+                        &gpu,
+                        max_queue,
+                        Some(&events),
+                    )
+                    .await?;
+                }
+                service::submit_solution_background(
+                    &client,
+                    &file_to_submit,
+                    &file_content,
+                    &leaderboard,
+                    &gpu,
+                    SubmissionMode::Test,
+                    Some(events),
+                )
+                .await
+            })
+        };
+        tokio::pin!(task);
+
+        let mut superseded = Vec::new();
+        let finished = loop {
+            tokio::select! {
+                joined = &mut task => break Some(joined),
+                _ = tokio::time::sleep(watch::WATCH_POLL_INTERVAL) => {
+                    if watcher.poll() {
+                        // Aborting only stops the local wait; the job is
+                        // cancelled below so it does not sit on the queue.
+                        superseded = service::active_jobs();
+                        task.abort();
+                        break None;
+                    }
+                }
+            }
+        };
+        // The run's sender is gone once it finished or was aborted.
+        let _ = renderer.await;
+
+        let outcome = match finished {
+            None => {
+                status_line.set(&format!("[watch] run {} superseded by new changes", run));
+                let cancelled = interrupt::cancel_jobs(&client, &superseded).await;
+                if !cancelled.is_empty() {
+                    status_line.set(&format!(
+                        "[watch] run {} superseded by new changes. {}",
+                        run,
+                        cancelled.join(" ")
+                    ));
+                }
+                status_line.finish();
+                continue;
+            }
+            Some(Ok(Ok(details))) => {
+                status_line.finish();
+                println!("\n{}", service::format_submission_details(&details)?);
+                "finished"
+            }
+            Some(Ok(Err(e))) => {
+                status_line.finish();
+                eprintln!("Run {} failed: {}", run, e);
+                "failed"
+            }
+            Some(Err(e)) => return Err(anyhow!("Task join error: {}", e)),
+        };
+
+        status_line.set(&format!(
+            "[watch] run {} {}. Waiting for changes...",
+            run, outcome
+        ));
+        while !watcher.poll() {
+            tokio::time::sleep(watch::WATCH_POLL_INTERVAL).await;
+        }
+        status_line.finish();
+    }
+}
+
+/// Checks run before the promoted leaderboard submission is uploaded.
+#[derive(Clone, Copy, Debug)]
+struct LeaderboardGuards {
+    allow_duplicate: bool,
+    confirmation: DiffConfirmation,
+}
+
+/// Resubmit the same bytes in leaderboard mode when a finished benchmark run's
+/// geomean beats the user's best leaderboard score for this leaderboard/GPU.
+///
+/// Benchmark and leaderboard runs time the same shapes, but the leaderboard
+/// score also reflects the secret run, so a win here is a strong hint rather
+/// than a guarantee.
+async fn promote_to_leaderboard(
+    client: &reqwest::Client,
+    benchmark_details: &crate::models::SubmissionDetails,
+    file_to_submit: &str,
+    file_content: &[u8],
+    leaderboard: &str,
+    gpu: &str,
+    guards: LeaderboardGuards,
+) -> Result<()> {
+    let geomean = baseline::benchmark_run(benchmark_details, gpu)
+        .and_then(|run| baseline::benchmark_geomean(&run.benchmarks))
+        .ok_or_else(|| {
+            anyhow!(
+                "Submission {} reported no benchmark timings; not promoting",
+                benchmark_details.id
+            )
+        })?;
+
+    match baseline::best_leaderboard_score(client, leaderboard, gpu).await? {
+        Some((best_id, best_score)) if geomean >= best_score => {
+            eprintln!(
+                "\nBenchmark geomean {} s does not beat your best leaderboard score {} s (submission {}). Not promoting.",
+                geomean, best_score, best_id
+            );
+            return Ok(());
+        }
+        Some((best_id, best_score)) => eprintln!(
+            "\nBenchmark geomean {} s beats your best leaderboard score {} s (submission {}, {:+.2}%). Promoting to leaderboard mode...",
+            geomean,
+            best_score,
+            best_id,
+            baseline::percent_slower(best_score, geomean)
+        ),
+        None => eprintln!(
+            "\nNo previous leaderboard score on {} for {}. Promoting benchmark geomean {} s to leaderboard mode...",
+            gpu, leaderboard, geomean
+        ),
+    }
+
+    if !guards.allow_duplicate {
+        if let Ok(Some(duplicate)) = find_duplicate_submission(
+            client,
+            file_content,
+            leaderboard,
+            gpu,
+            SubmissionMode::Leaderboard,
+        )
+        .await
+        {
+            return Err(anyhow!(duplicate.refusal_message()));
+        }
+    }
+    leaderboard_diff::confirm_leaderboard_submit(
+        client,
+        file_to_submit,
+        file_content,
+        leaderboard,
+        gpu,
+        guards.confirmation,
+    )
+    .await?;
+
+    let (events, printer) = progress::print_to_stderr("");
+    let leaderboard_details = service::submit_solution_background(
+        client,
+        file_to_submit,
+        file_content,
+        leaderboard,
+        gpu,
+        SubmissionMode::Leaderboard,
+        Some(events),
+    )
+    .await;
+    let _ = printer.await;
+    let leaderboard_details = leaderboard_details?;
+
+    println!(
+        "\nBenchmark submission {}: geomean {} s",
+        benchmark_details.id, geomean
+    );
+    println!(
+        "Leaderboard submission {}:\n{}",
+        leaderboard_details.id,
+        service::format_submission_details(&leaderboard_details)?
+    );
+    Ok(())
+}
+
+/// Refuse a mode the server says this leaderboard/GPU does not offer. A
+/// server that does not report its modes, or a failed lookup, lets it through.
+async fn ensure_mode_supported(
+    client: &reqwest::Client,
+    leaderboard: &str,
+    gpu: &str,
+    mode: SubmissionMode,
+) -> Result<()> {
+    match service::fetch_supported_modes(client, leaderboard, gpu).await {
+        Ok(Some(supported)) if !supported.modes.contains(&mode) => {
+            let available: Vec<&str> = supported.modes.iter().map(|m| m.as_str()).collect();
+            Err(anyhow!(
+                "{} mode is not available for {} on {}. Available modes: {}",
+                mode,
+                leaderboard,
+                gpu,
+                if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                }
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// A previous submission that ran byte-identical code on the same target.
+#[derive(Debug)]
+struct DuplicateSubmission {
+    submission_id: i64,
+    mode: SubmissionMode,
+    gpu: String,
+    score: Option<f64>,
+}
+
+impl DuplicateSubmission {
+    fn refusal_message(&self) -> String {
+        let score = self
+            .score
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        format!(
+            "Identical code already ran in {} mode on {} as submission {} (score: {}). \
+             Pass --allow-duplicate to submit it again.",
+            self.mode, self.gpu, self.submission_id, score
+        )
+    }
+}
+
+/// Look for an earlier submission of the same bytes that already ran in the
+/// same mode on the same GPU. Candidates from the local submission history
+/// are checked first; only if none matches are the user's most recent server
+/// submissions checked, a few at a time. Each candidate is confirmed against
+/// the code and runs the server has on record.
+async fn find_duplicate_submission(
+    client: &reqwest::Client,
+    file_content: &[u8],
+    leaderboard: &str,
+    gpu: &str,
+    mode: SubmissionMode,
+) -> Result<Option<DuplicateSubmission>> {
+    // Profile runs stream their results and never get a submission to compare against.
+    if mode == SubmissionMode::Profile {
+        return Ok(None);
+    }
+
+    let hash = history::content_hash(file_content);
+    let local: Vec<i64> = history::find_matching_records(&hash, leaderboard, gpu, mode.as_str())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|record| record.submission_id)
+        .collect();
+    for &submission_id in &local {
+        if let Some(duplicate) = confirm_duplicate(client, submission_id, &hash, gpu, mode).await {
+            return Ok(Some(duplicate));
+        }
+    }
+
+    let recent = service::get_user_submissions(
+        client,
+        Some(leaderboard),
+        Some(DUPLICATE_LOOKBACK_SUBMISSIONS),
+    )
+    .await?;
+    let remote = recent
+        .into_iter()
+        .filter(|submission| {
+            submission
+                .runs
+                .iter()
+                .any(|run| run.gpu_type.eq_ignore_ascii_case(gpu))
+                && !local.contains(&submission.id)
+        })
+        .map(|submission| submission.id);
+    // Newest first: `buffered` keeps the order, so the first match is the
+    // latest duplicate, and lookups stop once it is found.
+    let duplicates = stream::iter(remote)
+        .map(|submission_id| confirm_duplicate(client, submission_id, &hash, gpu, mode))
+        .buffered(DUPLICATE_LOOKUP_CONCURRENCY)
+        .filter_map(future::ready);
+    let mut duplicates = pin!(duplicates);
+    Ok(duplicates.next().await)
+}
+
+/// `submission_id` as a duplicate if the server has the same code on record
+/// with a run in `mode` on `gpu`. A failed lookup counts as no match.
+async fn confirm_duplicate(
+    client: &reqwest::Client,
+    submission_id: i64,
+    hash: &str,
+    gpu: &str,
+    mode: SubmissionMode,
+) -> Option<DuplicateSubmission> {
+    let details = service::get_user_submission(client, submission_id)
+        .await
+        .ok()?;
+    if history::content_hash(details.code.as_bytes()) != hash {
+        return None;
+    }
+    let matching_runs: Vec<_> = details
+        .runs
+        .iter()
+        .filter(|run| {
+            run.mode.eq_ignore_ascii_case(mode.as_str()) && run.runner.eq_ignore_ascii_case(gpu)
+        })
+        .collect();
+    if matching_runs.is_empty() {
+        return None;
+    }
+    let score = matching_runs
+        .iter()
+        .filter_map(|run| run.score)
+        .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    Some(DuplicateSubmission {
+        submission_id,
+        mode,
+        gpu: gpu.to_string(),
+        score,
+    })
+}
+
 #[derive(Debug)]
 struct ProfileReportLink {
     file_url: String,
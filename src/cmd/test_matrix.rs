@@ -0,0 +1,238 @@
+use anyhow::{anyhow, Result};
+use futures_util::future::join_all;
+use std::path::Path;
+
+use crate::cmd::interrupt;
+use crate::cmd::progress;
+use crate::models::{SubmissionDetails, SubmissionMode, TestResult};
+use crate::service;
+use crate::utils;
+
+/// Row label for a GPU's overall verdict, below the per-test rows.
+const OVERALL_ROW: &str = "(overall)";
+
+/// What one GPU's test-mode run produced.
+#[derive(Debug)]
+enum ColumnOutcome {
+    Ran {
+        tests: Vec<TestResult>,
+        passed: bool,
+    },
+    Error(String),
+}
+
+#[derive(Debug)]
+struct MatrixColumn {
+    gpu: String,
+    outcome: ColumnOutcome,
+}
+
+impl MatrixColumn {
+    fn new(gpu: String, result: Result<SubmissionDetails>) -> Self {
+        let outcome = match result {
+            Ok(details) => match details
+                .runs
+                .iter()
+                .find(|run| run.mode == "test" && run.runner.eq_ignore_ascii_case(&gpu))
+            {
+                Some(run) => ColumnOutcome::Ran {
+                    tests: run.tests.clone(),
+                    passed: run.passed,
+                },
+                None => ColumnOutcome::Error(format!(
+                    "Submission {} has no test run on {}",
+                    details.id, gpu
+                )),
+            },
+            Err(e) => ColumnOutcome::Error(e.to_string()),
+        };
+        Self { gpu, outcome }
+    }
+
+    fn failed(&self) -> bool {
+        match &self.outcome {
+            ColumnOutcome::Ran { tests, passed } => !passed || tests.iter().any(|t| !t.passed),
+            ColumnOutcome::Error(_) => true,
+        }
+    }
+
+    fn cell(&self, spec: &str) -> &'static str {
+        match &self.outcome {
+            ColumnOutcome::Error(_) => "ERROR",
+            ColumnOutcome::Ran { .. } if spec == OVERALL_ROW => {
+                if self.failed() {
+                    "FAIL"
+                } else {
+                    "pass"
+                }
+            }
+            ColumnOutcome::Ran { tests, .. } => match tests.iter().find(|t| t.spec == spec) {
+                Some(test) if test.passed => "pass",
+                Some(_) => "FAIL",
+                None => "-",
+            },
+        }
+    }
+}
+
+pub async fn run_test_matrix(
+    cli_id: String,
+    filepath: String,
+    leaderboard: Option<String>,
+) -> Result<()> {
+    if !Path::new(&filepath).exists() {
+        return Err(anyhow!("File not found: {}", filepath));
+    }
+    let (directives, _) = utils::get_popcorn_directives(&filepath)?;
+    let leaderboard = leaderboard
+        .or_else(|| Some(directives.leaderboard_name).filter(|name| !name.is_empty()))
+        .ok_or_else(|| {
+            anyhow!("Leaderboard not specified. Use --leaderboard flag or add leaderboard directive to file")
+        })?;
+    let file_content = std::fs::read(&filepath)?;
+
+    let client = service::create_client(Some(cli_id))?;
+    let mut interrupts = interrupt::interrupts()?;
+
+    let gpus: Vec<String> = service::fetch_gpus(&client, &leaderboard)
+        .await?
+        .into_iter()
+        .map(|gpu| gpu.title_text)
+        .collect();
+    if gpus.is_empty() {
+        return Err(anyhow!("Leaderboard {} has no GPUs", leaderboard));
+    }
+    eprintln!(
+        "Running tests for {} on {} GPU(s): {}",
+        leaderboard,
+        gpus.len(),
+        gpus.join(", ")
+    );
+
+    let runs = join_all(gpus.iter().map(|gpu| {
+        let (events, printer) = progress::print_to_stderr(&format!("[{}] ", gpu));
+        let run = service::submit_solution_background(
+            &client,
+            &filepath,
+            &file_content,
+            &leaderboard,
+            gpu,
+            SubmissionMode::Test,
+            Some(events),
+        );
+        async move {
+            let result = run.await;
+            let _ = printer.await;
+            result
+        }
+    }));
+    tokio::pin!(runs);
+    let results = tokio::select! {
+        results = &mut runs => results,
+        Some(()) = interrupts.recv() => return interrupt::cancel_on_interrupt(&client, &mut interrupts).await,
+    };
+
+    let columns: Vec<MatrixColumn> = gpus
+        .iter()
+        .zip(results)
+        .map(|(gpu, result)| MatrixColumn::new(gpu.clone(), result))
+        .collect();
+
+    println!("\n{}", render_matrix(&columns));
+    for column in &columns {
+        if let ColumnOutcome::Error(error) = &column.outcome {
+            eprintln!("{}: {}", column.gpu, error);
+        }
+    }
+
+    let failed = columns.iter().filter(|c| c.failed()).count();
+    if failed > 0 {
+        return Err(anyhow!(
+            "Tests failed on {} of {} GPU(s)",
+            failed,
+            columns.len()
+        ));
+    }
+    Ok(())
+}
+
+/// GPU × test-case grid: one row per spec in first-seen order plus an overall
+/// row, one column per GPU.
+fn render_matrix(columns: &[MatrixColumn]) -> String {
+    let mut specs: Vec<&str> = Vec::new();
+    for column in columns {
+        if let ColumnOutcome::Ran { tests, .. } = &column.outcome {
+            for test in tests {
+                if !specs.contains(&test.spec.as_str()) {
+                    specs.push(&test.spec);
+                }
+            }
+        }
+    }
+    specs.push(OVERALL_ROW);
+
+    let label_width = specs.iter().map(|s| s.len()).max().unwrap_or(0).max(4);
+    let widths: Vec<usize> = columns.iter().map(|c| c.gpu.len().max(5)).collect();
+
+    let mut header = format!("{:<label_width$}", "Test");
+    for (column, width) in columns.iter().zip(&widths) {
+        header.push_str(&format!("  {:<width$}", column.gpu));
+    }
+    let mut lines = vec![header.trim_end().to_string()];
+    for spec in specs {
+        let mut line = format!("{:<label_width$}", spec);
+        for (column, width) in columns.iter().zip(&widths) {
+            line.push_str(&format!("  {:<width$}", column.cell(spec)));
+        }
+        lines.push(line.trim_end().to_string());
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test(spec: &str, passed: bool) -> TestResult {
+        TestResult {
+            spec: spec.to_string(),
+            passed,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_matrix_marks_failures_and_missing_cells() {
+        let columns = vec![
+            MatrixColumn {
+                gpu: "B200".to_string(),
+                outcome: ColumnOutcome::Ran {
+                    tests: vec![test("k: 8", true), test("k: 64", true)],
+                    passed: true,
+                },
+            },
+            MatrixColumn {
+                gpu: "MI300".to_string(),
+                outcome: ColumnOutcome::Ran {
+                    tests: vec![test("k: 8", false)],
+                    passed: false,
+                },
+            },
+            MatrixColumn {
+                gpu: "H100".to_string(),
+                outcome: ColumnOutcome::Error("compile error".to_string()),
+            },
+        ];
+
+        assert_eq!(
+            render_matrix(&columns),
+            "Test       B200   MI300  H100\n\
+             k: 8       pass   FAIL   ERROR\n\
+             k: 64      pass   -      ERROR\n\
+             (overall)  pass   FAIL   ERROR"
+        );
+        assert!(!columns[0].failed());
+        assert!(columns[1].failed());
+        assert!(columns[2].failed());
+    }
+}
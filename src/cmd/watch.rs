@@ -0,0 +1,232 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime};
+
+use tokio::time::Duration;
+
+/// How often the watched files' metadata is checked.
+pub const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long the watched files must stay unchanged before a resubmit, so an
+/// editor's save (often several writes) triggers a single run.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(750);
+
+/// Upper bound on files discovered through local imports/includes.
+const MAX_WATCHED_FILES: usize = 64;
+
+type Fingerprint = Option<(SystemTime, u64)>;
+
+/// Polls a submission and its locally included sources for changes.
+#[derive(Debug)]
+pub struct FileWatcher {
+    root: PathBuf,
+    files: Vec<PathBuf>,
+    fingerprints: HashMap<PathBuf, Fingerprint>,
+    last_change: Option<Instant>,
+}
+
+impl FileWatcher {
+    pub fn new<P: AsRef<Path>>(submission: P) -> Self {
+        let root = submission.as_ref().to_path_buf();
+        let files = watched_files(&root);
+        let fingerprints = fingerprint_all(&files);
+        Self {
+            root,
+            files,
+            fingerprints,
+            last_change: None,
+        }
+    }
+
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Returns true once a change has settled for the debounce period.
+    pub fn poll(&mut self) -> bool {
+        self.poll_at(Instant::now())
+    }
+
+    fn poll_at(&mut self, now: Instant) -> bool {
+        let current = fingerprint_all(&self.files);
+        if current != self.fingerprints {
+            self.fingerprints = current;
+            self.last_change = Some(now);
+            return false;
+        }
+
+        match self.last_change {
+            Some(changed) if now.duration_since(changed) >= WATCH_DEBOUNCE => {
+                self.last_change = None;
+                // Imports may have been added or removed by the edit.
+                self.files = watched_files(&self.root);
+                self.fingerprints = fingerprint_all(&self.files);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn fingerprint_all(files: &[PathBuf]) -> HashMap<PathBuf, Fingerprint> {
+    files
+        .iter()
+        .map(|path| {
+            let fingerprint = fs::metadata(path)
+                .ok()
+                .and_then(|meta| meta.modified().ok().map(|modified| (modified, meta.len())));
+            (path.clone(), fingerprint)
+        })
+        .collect()
+}
+
+/// The submission plus any sources it pulls in from its own directory tree:
+/// Python `import x` / `from .x import y` resolving to `x.py` or
+/// `x/__init__.py`, and C/C++ `#include "x.h"`. Archives are watched as-is.
+fn watched_files(submission: &Path) -> Vec<PathBuf> {
+    let mut files = vec![submission.to_path_buf()];
+    if crate::utils::is_archive_file(submission) {
+        return files;
+    }
+
+    let mut queue = VecDeque::from([submission.to_path_buf()]);
+    while let Some(path) = queue.pop_front() {
+        let Ok(source) = fs::read_to_string(&path) else {
+            continue;
+        };
+        for include in local_includes(&path, &source) {
+            if files.len() >= MAX_WATCHED_FILES {
+                return files;
+            }
+            if !files.contains(&include) {
+                files.push(include.clone());
+                queue.push_back(include);
+            }
+        }
+    }
+    files
+}
+
+fn local_includes(path: &Path, source: &str) -> Vec<PathBuf> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut found = Vec::new();
+
+    for line in source.lines() {
+        let line = line.trim();
+        let modules: Vec<&str> = if let Some(rest) = line.strip_prefix("from ") {
+            rest.split_whitespace().next().into_iter().collect()
+        } else if let Some(rest) = line.strip_prefix("import ") {
+            rest.split(',')
+                .filter_map(|part| part.split_whitespace().next())
+                .collect()
+        } else if let Some(rest) = line.strip_prefix("#include") {
+            let rest = rest.trim();
+            if let Some(header) = rest.strip_prefix('"').and_then(|r| r.split('"').next()) {
+                let candidate = dir.join(header);
+                if candidate.is_file() {
+                    found.push(candidate);
+                }
+            }
+            continue;
+        } else {
+            continue;
+        };
+
+        for module in modules {
+            let relative = module.trim_start_matches('.').replace('.', "/");
+            if relative.is_empty() {
+                continue;
+            }
+            let candidates = [
+                dir.join(format!("{}.py", relative)),
+                dir.join(&relative).join("__init__.py"),
+            ];
+            if let Some(candidate) = candidates.into_iter().find(|c| c.is_file()) {
+                found.push(candidate);
+            }
+        }
+    }
+    found
+}
+
+/// A single status line on stderr, redrawn in place on a terminal and
+/// printed line by line otherwise (e.g. when piped into a log).
+pub struct StatusLine {
+    in_place: bool,
+}
+
+impl StatusLine {
+    pub fn on_stderr() -> Self {
+        Self {
+            in_place: io::stderr().is_terminal(),
+        }
+    }
+
+    pub fn set(&self, text: &str) {
+        if self.in_place {
+            let mut stderr = io::stderr();
+            let _ = write!(stderr, "\r\x1b[2K{}", text);
+            let _ = stderr.flush();
+        } else {
+            eprintln!("{}", text);
+        }
+    }
+
+    /// End the in-place line so regular output starts on a fresh line.
+    pub fn finish(&self) {
+        if self.in_place {
+            eprintln!();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_watched_files_follow_local_imports() {
+        let dir = tempdir().unwrap();
+        let submission = dir.path().join("submission.py");
+        fs::write(
+            &submission,
+            "import torch, helpers\nfrom kernels.gemm import run\nfrom .missing import x\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("helpers.py"), "#include \"common.h\"\n").unwrap();
+        fs::create_dir(dir.path().join("kernels")).unwrap();
+        fs::write(dir.path().join("kernels").join("gemm.py"), "").unwrap();
+        fs::write(dir.path().join("common.h"), "").unwrap();
+
+        let files = watched_files(&submission);
+        assert_eq!(
+            files,
+            vec![
+                submission.clone(),
+                dir.path().join("helpers.py"),
+                dir.path().join("kernels").join("gemm.py"),
+                dir.path().join("common.h"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_poll_debounces_changes() {
+        let dir = tempdir().unwrap();
+        let submission = dir.path().join("submission.py");
+        fs::write(&submission, "a").unwrap();
+        let mut watcher = FileWatcher::new(&submission);
+
+        let start = Instant::now();
+        assert!(!watcher.poll_at(start));
+
+        fs::write(&submission, "ab").unwrap();
+        assert!(!watcher.poll_at(start));
+        assert!(!watcher.poll_at(start + WATCH_DEBOUNCE / 2));
+        assert!(watcher.poll_at(start + WATCH_DEBOUNCE));
+        assert!(!watcher.poll_at(start + WATCH_DEBOUNCE * 2));
+    }
+}
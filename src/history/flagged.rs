@@ -0,0 +1,57 @@
+use anyhow::Result;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use super::{append_record, load_records, popcorn_file, SubmissionRecord};
+use crate::models::HackFinding;
+
+/// A submission the server judged `hacked`, kept locally so the verdict can be
+/// reviewed later with `popcorn submissions flagged`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FlaggedVerdict {
+    pub submission_id: i64,
+    pub leaderboard: String,
+    pub gpu: String,
+    pub mode: String,
+    pub file_name: String,
+    pub content_hash: String,
+    pub error: Option<String>,
+    pub findings: Vec<HackFinding>,
+    /// The explanation shown when the verdict came in, with local line links.
+    pub explanation: String,
+    pub flagged_at: String,
+}
+
+impl FlaggedVerdict {
+    pub fn new(
+        record: &SubmissionRecord,
+        error: Option<&str>,
+        findings: &[HackFinding],
+        explanation: String,
+    ) -> Self {
+        Self {
+            submission_id: record.submission_id,
+            leaderboard: record.leaderboard.clone(),
+            gpu: record.gpu.clone(),
+            mode: record.mode.clone(),
+            file_name: record.file_name.clone(),
+            content_hash: record.content_hash.clone(),
+            error: error.map(str::to_string),
+            findings: findings.to_vec(),
+            explanation,
+            flagged_at: Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// Append a verdict to `~/.popcorn/flagged.jsonl`.
+pub fn record_flagged(verdict: &FlaggedVerdict) -> Result<()> {
+    append_record(&popcorn_file("flagged.jsonl")?, verdict)
+}
+
+/// Locally recorded verdicts, newest first.
+pub fn load_flagged() -> Result<Vec<FlaggedVerdict>> {
+    let mut verdicts: Vec<FlaggedVerdict> = load_records(&popcorn_file("flagged.jsonl")?)?;
+    verdicts.reverse();
+    Ok(verdicts)
+}
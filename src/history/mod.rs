@@ -0,0 +1,196 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub mod flagged;
+pub mod runs;
+
+/// A submission accepted by the server, recorded locally so later submits can
+/// recognise byte-identical code without a server round trip.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SubmissionRecord {
+    pub submission_id: i64,
+    pub leaderboard: String,
+    pub gpu: String,
+    pub mode: String,
+    pub content_hash: String,
+    pub file_name: String,
+    pub submitted_at: String,
+}
+
+impl SubmissionRecord {
+    pub fn new(
+        submission_id: i64,
+        leaderboard: &str,
+        gpu: &str,
+        mode: &str,
+        file_content: &[u8],
+        file_name: &str,
+    ) -> Self {
+        Self {
+            submission_id,
+            leaderboard: leaderboard.to_string(),
+            gpu: gpu.to_string(),
+            mode: mode.to_lowercase(),
+            content_hash: content_hash(file_content),
+            file_name: file_name.to_string(),
+            submitted_at: Utc::now().to_rfc3339(),
+        }
+    }
+
+    fn matches(&self, content_hash: &str, leaderboard: &str, gpu: &str, mode: &str) -> bool {
+        self.content_hash == content_hash
+            && self.leaderboard.eq_ignore_ascii_case(leaderboard)
+            && self.gpu.eq_ignore_ascii_case(gpu)
+            && self.mode.eq_ignore_ascii_case(mode)
+    }
+}
+
+/// Hex-encoded SHA-256 of a submission's bytes.
+pub fn content_hash(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+/// Key sent as `Idempotency-Key` with one submit attempt: the code, its
+/// target and a per-attempt nonce, so retries share a key and new attempts
+/// do not.
+pub fn idempotency_key(
+    content: &[u8],
+    leaderboard: &str,
+    gpu: &str,
+    mode: &str,
+    nonce: u64,
+) -> String {
+    let material = format!(
+        "{}:{}:{}:{}:{:016x}",
+        content_hash(content),
+        leaderboard.to_lowercase(),
+        gpu.to_lowercase(),
+        mode.to_lowercase(),
+        nonce
+    );
+    content_hash(material.as_bytes())
+}
+
+/// A file under `~/.popcorn`.
+fn popcorn_file(name: &str) -> Result<PathBuf> {
+    dirs::home_dir()
+        .map(|mut path| {
+            path.push(".popcorn");
+            path.push(name);
+            path
+        })
+        .ok_or_else(|| anyhow!("Could not find home directory"))
+}
+
+fn history_path() -> Result<PathBuf> {
+    popcorn_file("submissions.jsonl")
+}
+
+/// Append a record to the local submission history (`~/.popcorn/submissions.jsonl`).
+pub fn record_submission(record: &SubmissionRecord) -> Result<()> {
+    append_record(&history_path()?, record)
+}
+
+/// Records for identical code previously submitted to the same target, newest first.
+pub fn find_matching_records(
+    content_hash: &str,
+    leaderboard: &str,
+    gpu: &str,
+    mode: &str,
+) -> Result<Vec<SubmissionRecord>> {
+    let mut records: Vec<SubmissionRecord> = load_records::<SubmissionRecord>(&history_path()?)?
+        .into_iter()
+        .filter(|record| record.matches(content_hash, leaderboard, gpu, mode))
+        .collect();
+    records.reverse();
+    Ok(records)
+}
+
+fn append_record<T: Serialize>(path: &Path, record: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| anyhow!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+    let line = serde_json::to_string(record)?;
+    writeln!(file, "{}", line).map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))
+}
+
+fn load_records<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    // Skip lines that fail to parse so one corrupt entry does not hide the rest.
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_content_hash_is_stable_sha256() {
+        assert_eq!(
+            content_hash(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_idempotency_key_depends_on_target_and_nonce() {
+        let key = idempotency_key(b"code", "qr_v2", "B200", "test", 7);
+        assert_eq!(key, idempotency_key(b"code", "QR_V2", "b200", "Test", 7));
+        assert_ne!(key, idempotency_key(b"code", "qr_v2", "B200", "test", 8));
+        assert_ne!(key, idempotency_key(b"code", "qr_v2", "H100", "test", 7));
+        assert_ne!(key, idempotency_key(b"other", "qr_v2", "B200", "test", 7));
+    }
+
+    #[test]
+    fn test_append_and_load_records_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("nested").join("submissions.jsonl");
+        let first = SubmissionRecord::new(1, "qr_v2", "B200", "Leaderboard", b"code", "a.py");
+        let second = SubmissionRecord::new(2, "qr_v2", "B200", "test", b"code", "a.py");
+
+        append_record(&path, &first).unwrap();
+        append_record(&path, &second).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+
+        let records: Vec<SubmissionRecord> = load_records(&path).unwrap();
+        assert_eq!(records, vec![first.clone(), second]);
+        assert_eq!(records[0].mode, "leaderboard");
+        assert!(records[0].matches(&content_hash(b"code"), "QR_V2", "b200", "leaderboard"));
+        assert!(!records[0].matches(&content_hash(b"other"), "qr_v2", "B200", "leaderboard"));
+    }
+
+    #[test]
+    fn test_load_records_missing_file_is_empty() {
+        let dir = tempdir().unwrap();
+        assert!(
+            load_records::<SubmissionRecord>(&dir.path().join("missing.jsonl"))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_RUNS_DIR: &str = ".popcorn/runs";
+
+/// Output of one run, saved as `<label>.stdout.log`, `<label>.stderr.log` and
+/// `<label>.result.json` under its submission's directory.
+#[derive(Debug)]
+pub struct RunLog<'a> {
+    /// File name stem, e.g. `1-test` or `2-leaderboard-secret`.
+    pub label: String,
+    pub stdout: Option<&'a str>,
+    pub stderr: Option<&'a str>,
+    pub result: &'a Value,
+}
+
+/// Root of the per-submission log directories: `POPCORN_RUNS_DIR`, or
+/// `.popcorn/runs` under the working directory.
+pub fn runs_root() -> PathBuf {
+    env::var("POPCORN_RUNS_DIR")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_RUNS_DIR))
+}
+
+/// Directory holding the logs of `submission_id`. The id becomes a path
+/// component, so anything that could climb out of `root` is rejected.
+pub fn submission_dir(root: &Path, submission_id: &str) -> Result<PathBuf> {
+    let valid = !submission_id.is_empty()
+        && submission_id != "."
+        && submission_id != ".."
+        && submission_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(anyhow!("Invalid submission id: {:?}", submission_id));
+    }
+    Ok(root.join(submission_id))
+}
+
+/// Write each run's stdout, stderr and raw result JSON under the submission's
+/// directory, replacing copies from an earlier save, and return the files
+/// written.
+pub fn save_run_logs(root: &Path, submission_id: &str, runs: &[RunLog]) -> Result<Vec<PathBuf>> {
+    let dir = submission_dir(root, submission_id)?;
+    fs::create_dir_all(&dir).map_err(|e| anyhow!("Failed to create {}: {}", dir.display(), e))?;
+    let mut files = Vec::new();
+    for run in runs {
+        let result = serde_json::to_string_pretty(run.result)?;
+        let outputs = [
+            ("stdout.log", run.stdout),
+            ("stderr.log", run.stderr),
+            ("result.json", Some(result.as_str())),
+        ];
+        for (suffix, content) in outputs {
+            let Some(content) = content else {
+                continue;
+            };
+            let path = dir.join(format!("{}.{}", run.label, suffix));
+            fs::write(&path, content)
+                .map_err(|e| anyhow!("Failed to write {}: {}", path.display(), e))?;
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// Saved log files of a submission in name order, or none if nothing was
+/// saved for it.
+pub fn saved_run_logs(root: &Path, submission_id: &str) -> Result<Vec<PathBuf>> {
+    let dir = submission_dir(root, submission_id)?;
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files = fs::read_dir(&dir)
+        .map_err(|e| anyhow!("Failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_save_and_list_run_logs() {
+        let root = tempdir().unwrap();
+        let result = json!({ "success": true });
+        let runs = [
+            RunLog {
+                label: "1-test".to_string(),
+                stdout: Some("all good\n"),
+                stderr: None,
+                result: &result,
+            },
+            RunLog {
+                label: "2-leaderboard".to_string(),
+                stdout: Some("timing\n"),
+                stderr: Some("warning\n"),
+                result: &result,
+            },
+        ];
+        let written = save_run_logs(root.path(), "42", &runs).unwrap();
+        assert_eq!(written.len(), 5);
+
+        let saved = saved_run_logs(root.path(), "42").unwrap();
+        let names: Vec<_> = saved
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "1-test.result.json",
+                "1-test.stdout.log",
+                "2-leaderboard.result.json",
+                "2-leaderboard.stderr.log",
+                "2-leaderboard.stdout.log",
+            ]
+        );
+        assert_eq!(fs::read_to_string(&saved[1]).unwrap(), "all good\n");
+        assert!(saved_run_logs(root.path(), "7").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_submission_dir_rejects_path_components() {
+        let root = Path::new("runs");
+        assert_eq!(
+            submission_dir(root, "job-1a2b").unwrap(),
+            root.join("job-1a2b")
+        );
+        for id in ["", "..", "../etc", "a/b"] {
+            assert!(submission_dir(root, id).is_err(), "{:?}", id);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 mod cmd;
+mod history;
 mod models;
 mod service;
 mod utils;
@@ -1,14 +1,20 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
 #[derive(Clone, Debug)]
 pub struct LeaderboardItem {
     pub title_text: String,
     pub task_description: String,
+    pub deadline: Option<String>,
 }
 
 impl LeaderboardItem {
-    pub fn new(title_text: String, task_description: String) -> Self {
+    pub fn new(title_text: String, task_description: String, deadline: Option<String>) -> Self {
         Self {
             title_text,
             task_description,
+            deadline,
         }
     }
 }
@@ -24,21 +30,105 @@ impl GpuItem {
     }
 }
 
+/// How a submission is run on the server.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, ValueEnum)]
+pub enum SubmissionMode {
+    /// Run the tests and report which passed or failed
+    Test,
+    /// Run the tests, then time the benchmark shapes
+    Benchmark,
+    /// Run public and secret tests and benchmarks, and rank the result
+    Leaderboard,
+    /// Profile the solution and download the profiler output
+    Profile,
+}
+
+impl SubmissionMode {
+    pub const ALL: [SubmissionMode; 4] = [
+        SubmissionMode::Test,
+        SubmissionMode::Benchmark,
+        SubmissionMode::Leaderboard,
+        SubmissionMode::Profile,
+    ];
+
+    /// The name used in server URLs, run records and `--mode`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SubmissionMode::Test => "test",
+            SubmissionMode::Benchmark => "benchmark",
+            SubmissionMode::Leaderboard => "leaderboard",
+            SubmissionMode::Profile => "profile",
+        }
+    }
+
+    /// Case-insensitive lookup of a server-reported mode name.
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.as_str().eq_ignore_ascii_case(value.trim()))
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            SubmissionMode::Test => "Test",
+            SubmissionMode::Benchmark => "Benchmark",
+            SubmissionMode::Leaderboard => "Leaderboard",
+            SubmissionMode::Profile => "Profile",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            SubmissionMode::Test => "Test the solution and give detailed results about passed/failed tests.",
+            SubmissionMode::Benchmark => "Benchmark the solution, this also runs the tests and afterwards runs the benchmark, returning detailed timing results",
+            SubmissionMode::Leaderboard => "Submit to the leaderboard, this first runs public tests and then private tests. If both pass, the submission is evaluated and submit to the leaderboard.",
+            SubmissionMode::Profile => "Profile the solution using Nsight Compute (NVIDIA) or rocPROF (AMD). Saves profiling data to .popcorn/artifacts/<job id>/ (or POPCORN_ARTIFACTS_DIR).",
+        }
+    }
+}
+
+impl fmt::Display for SubmissionMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Modes and profilers a leaderboard/GPU pair accepts, as reported by the
+/// server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SupportedModes {
+    pub modes: Vec<SubmissionMode>,
+    pub profilers: Vec<String>,
+}
+
 #[derive(Clone, Debug)]
 pub struct SubmissionModeItem {
     pub title_text: String,
     pub description_text: String,
-    pub value: String,
+    pub value: SubmissionMode,
 }
 
 impl SubmissionModeItem {
-    pub fn new(title_text: String, description_text: String, value: String) -> Self {
+    pub fn new(title_text: String, description_text: String, value: SubmissionMode) -> Self {
         Self {
             title_text,
             description_text,
             value,
         }
     }
+
+    /// The TUI entry for `mode`, naming the available profilers for profile mode.
+    pub fn for_mode(mode: SubmissionMode, profilers: &[String]) -> Self {
+        let description = if mode == SubmissionMode::Profile && !profilers.is_empty() {
+            format!(
+                "Profile the solution ({}). Saves profiling data to .popcorn/artifacts/<job id>/ (or POPCORN_ARTIFACTS_DIR).",
+                profilers.join(", ")
+            )
+        } else {
+            mode.description().to_string()
+        };
+        Self::new(mode.title().to_string(), description, mode)
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
@@ -47,6 +137,7 @@ pub enum AppState {
     LeaderboardSelection,
     GpuSelection,
     SubmissionModeSelection,
+    ConfirmLeaderboardDiff,
     WaitingForResult,
 }
 
@@ -68,8 +159,9 @@ pub struct UserSubmissionRun {
     pub score: Option<f64>,
 }
 
-/// Full submission details including code and runs
-#[derive(Clone, Debug)]
+/// Full submission details including code and runs. Serializes without the
+/// code, for machine-readable output.
+#[derive(Clone, Debug, Serialize)]
 pub struct SubmissionDetails {
     pub id: i64,
     pub leaderboard_id: i64,
@@ -78,19 +170,35 @@ pub struct SubmissionDetails {
     pub user_id: String,
     pub submission_time: String,
     pub done: bool,
+    #[serde(skip)]
     pub code: String,
     pub runs: Vec<SubmissionRun>,
     pub job: Option<SubmissionJobStatus>,
     pub runner_queue: Option<RunnerQueueStatus>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct SubmissionJobStatus {
     pub status: Option<String>,
     pub error: Option<String>,
+    /// What the anti-cheat checks found, for `hacked` jobs.
+    pub findings: Vec<HackFinding>,
 }
 
-#[derive(Clone, Debug)]
+/// One anti-cheat check that tripped on a submission, as far as the server
+/// describes it.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct HackFinding {
+    /// Name of the check, e.g. `stream_tampering`.
+    pub check: String,
+    pub message: Option<String>,
+    /// The offending pattern, and where the server found it in the code.
+    pub pattern: Option<String>,
+    pub line: Option<usize>,
+    pub snippet: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize)]
 pub struct RunnerQueueStatus {
     pub runner: Option<String>,
     pub gpu: Option<String>,
@@ -98,7 +206,7 @@ pub struct RunnerQueueStatus {
 }
 
 /// A single run within a submission
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct SubmissionRun {
     pub start_time: Option<String>,
     pub end_time: Option<String>,
@@ -107,4 +215,87 @@ pub struct SubmissionRun {
     pub runner: String,
     pub score: Option<f64>,
     pub passed: bool,
+    pub tests: Vec<TestResult>,
+    pub benchmarks: Vec<BenchmarkResult>,
+    /// Raw output of the run, when the server includes it.
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+    /// The run's `result` object as the server sent it.
+    #[serde(skip)]
+    pub raw_result: serde_json::Value,
+}
+
+/// Outcome of one test case of a run, as reported under the run's
+/// `test.{i}.*` result keys.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct TestResult {
+    pub spec: String,
+    /// Status as reported, e.g. `pass` or `fail`.
+    pub status: String,
+    pub passed: bool,
+    pub error: Option<String>,
+    /// Largest absolute and relative deviation from the reference output,
+    /// when the checker reports them.
+    pub max_abs_error: Option<f64>,
+    pub max_rel_error: Option<f64>,
+}
+
+/// Timing statistics for one benchmark shape of a run, as reported under the
+/// run's `benchmark.{i}.*` result keys. Times are in nanoseconds; `min` and
+/// `max` are the best and worst observed iterations.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct BenchmarkResult {
+    pub spec: String,
+    pub mean: Option<f64>,
+    pub std: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub runs: Option<u64>,
+}
+
+/// Structured contents of a run's `result` object.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunResults {
+    pub tests: Vec<TestResult>,
+    pub benchmarks: Vec<BenchmarkResult>,
+}
+
+/// Submission fixtures shared by the unit tests of modules that read runs.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::{SubmissionDetails, SubmissionRun};
+
+    /// A passed run on B200 with no tests or benchmarks.
+    pub fn run(mode: &str, secret: bool, score: Option<f64>) -> SubmissionRun {
+        SubmissionRun {
+            start_time: None,
+            end_time: None,
+            mode: mode.to_string(),
+            secret,
+            runner: "B200".to_string(),
+            score,
+            passed: true,
+            tests: Vec::new(),
+            benchmarks: Vec::new(),
+            stdout: None,
+            stderr: None,
+            raw_result: serde_json::Value::Null,
+        }
+    }
+
+    pub fn details(id: i64, runs: Vec<SubmissionRun>) -> SubmissionDetails {
+        SubmissionDetails {
+            id,
+            leaderboard_id: 1,
+            leaderboard_name: "qr_v2".to_string(),
+            file_name: "submission.py".to_string(),
+            user_id: "u".to_string(),
+            submission_time: String::new(),
+            done: true,
+            code: String::new(),
+            runs,
+            job: None,
+            runner_queue: None,
+        }
+    }
 }
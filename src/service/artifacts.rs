@@ -0,0 +1,351 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+const DEFAULT_ARTIFACTS_DIR: &str = ".popcorn/artifacts";
+
+/// Bounds on what extracting one profiler archive may write. Entry headers
+/// are not trusted: sizes are counted as the data is decompressed.
+#[derive(Clone, Copy, Debug)]
+pub(super) struct ExtractLimits {
+    pub max_entries: usize,
+    pub max_total_bytes: u64,
+}
+
+impl Default for ExtractLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 1024,
+            max_total_bytes: 4 << 30,
+        }
+    }
+}
+
+/// Directory the artifacts of profiler job `job_id` are written to:
+/// `<root>/<job_id>`, where the root is `POPCORN_ARTIFACTS_DIR` or
+/// `.popcorn/artifacts` under the working directory.
+pub(super) fn artifacts_dir(job_id: &str) -> Result<PathBuf> {
+    let root = env::var("POPCORN_ARTIFACTS_DIR")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_ARTIFACTS_DIR));
+    Ok(root.join(sanitize_artifact_name(job_id)?))
+}
+
+/// A server- or archive-provided name reduced to one safe file name: only
+/// the last path component is kept, characters outside `[A-Za-z0-9._-]`
+/// become `_` and leading dots are dropped, so the result can neither leave
+/// its directory nor hide in it.
+pub(super) fn sanitize_artifact_name(name: &str) -> Result<String> {
+    let last = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let sanitized: String = last
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || matches!(ch, '.' | '_' | '-') {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let sanitized = sanitized.trim_start_matches('.');
+    if sanitized.is_empty() {
+        return Err(anyhow!("Invalid profile artifact name: {:?}", name));
+    }
+    Ok(sanitized.to_string())
+}
+
+/// Sanitize each of a job's artifact names and give the ones that collide an
+/// index suffix before the extension, so no two downloads share a file.
+/// Names are compared case-insensitively for case-insensitive filesystems.
+pub(super) fn unique_artifact_names<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+) -> Result<Vec<String>> {
+    let mut taken = HashSet::new();
+    names
+        .into_iter()
+        .enumerate()
+        .map(|(idx, name)| {
+            let mut file_name = sanitize_artifact_name(name)?;
+            if taken.contains(&file_name.to_lowercase()) {
+                let (stem, extension) = match file_name.rsplit_once('.') {
+                    Some((stem, extension)) => (stem.to_string(), format!(".{}", extension)),
+                    None => (file_name.clone(), String::new()),
+                };
+                let mut suffix = idx;
+                loop {
+                    file_name = format!("{}-{}{}", stem, suffix, extension);
+                    if !taken.contains(&file_name.to_lowercase()) {
+                        break;
+                    }
+                    suffix += 1;
+                }
+            }
+            taken.insert(file_name.to_lowercase());
+            Ok(file_name)
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+pub(super) struct ExtractedProfileArtifacts {
+    pub details: Vec<PathBuf>,
+    pub reports: Vec<PathBuf>,
+}
+
+/// Extract the Nsight Compute details and reports in `zip_path` into a
+/// directory next to it. Archives with symlink entries, or more entries or
+/// data than `limits` allow, are rejected. Each file is written under a
+/// temporary name and renamed once complete.
+pub(super) fn extract_profile_artifacts(
+    zip_path: &Path,
+    limits: ExtractLimits,
+) -> Result<ExtractedProfileArtifacts> {
+    let file = File::open(zip_path)
+        .map_err(|e| anyhow!("Failed to open {}: {}", zip_path.display(), e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| {
+        anyhow!(
+            "Failed to read profile artifact {}: {}",
+            zip_path.display(),
+            e
+        )
+    })?;
+    if archive.len() > limits.max_entries {
+        return Err(anyhow!(
+            "Profile artifact {} has {} entries, more than the limit of {}",
+            zip_path.display(),
+            archive.len(),
+            limits.max_entries
+        ));
+    }
+    let extract_dir = zip_path.with_extension("");
+    fs::create_dir_all(&extract_dir).map_err(|e| {
+        anyhow!(
+            "Failed to create profile report directory {}: {}",
+            extract_dir.display(),
+            e
+        )
+    })?;
+
+    let mut details = Vec::new();
+    let mut reports = Vec::new();
+    let mut remaining = limits.max_total_bytes;
+    for idx in 0..archive.len() {
+        let mut entry = archive.by_index(idx).map_err(|e| {
+            anyhow!(
+                "Failed to read profile artifact entry in {}: {}",
+                zip_path.display(),
+                e
+            )
+        })?;
+        if entry.is_symlink() {
+            return Err(anyhow!(
+                "Profile artifact {} contains a symlink entry ({})",
+                zip_path.display(),
+                entry.name()
+            ));
+        }
+        if entry.is_dir()
+            || !(entry.name().ends_with(".ncu-rep")
+                || entry.name().ends_with("ncu-details.txt")
+                || entry.name().ends_with("ncu-details.csv"))
+        {
+            continue;
+        }
+
+        let file_name = sanitize_artifact_name(entry.name())?;
+        let mut output_path = extract_dir.join(&file_name);
+        if output_path.exists() {
+            let stem = output_path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("profile");
+            let extension = output_path
+                .extension()
+                .and_then(|s| s.to_str())
+                .map(|ext| format!(".{}", ext))
+                .unwrap_or_default();
+            output_path = extract_dir.join(format!("{}-{}{}", stem, idx, extension));
+        }
+
+        let written = write_atomically(&output_path, &mut entry, remaining)?;
+        remaining -= written;
+        if output_path.extension().and_then(|s| s.to_str()) == Some("ncu-rep") {
+            reports.push(output_path);
+        } else {
+            details.push(output_path);
+        }
+    }
+    Ok(ExtractedProfileArtifacts { details, reports })
+}
+
+/// Copy at most `limit` bytes of `reader` to `path` through a temporary file
+/// in the same directory, and return the bytes written. Exceeding the limit
+/// fails without leaving anything at `path`.
+fn write_atomically(path: &Path, reader: &mut impl Read, limit: u64) -> Result<u64> {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp_path = path.with_file_name(format!(".{}.tmp", file_name));
+    let result = (|| {
+        let mut output = File::create(&tmp_path)?;
+        let written = io::copy(&mut reader.take(limit.saturating_add(1)), &mut output)?;
+        if written > limit {
+            return Err(io::Error::other(format!(
+                "archive expands beyond the {} byte limit",
+                limit
+            )));
+        }
+        output.sync_all()?;
+        fs::rename(&tmp_path, path)?;
+        Ok(written)
+    })();
+    result.map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        anyhow!("Failed to extract {}: {}", path.display(), e)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    fn write_zip(path: &Path, build: impl FnOnce(&mut ZipWriter<File>)) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        build(&mut zip);
+        zip.finish().unwrap();
+    }
+
+    fn add_file(zip: &mut ZipWriter<File>, name: &str, data: &[u8]) {
+        zip.start_file(name, SimpleFileOptions::default()).unwrap();
+        zip.write_all(data).unwrap();
+    }
+
+    fn files_in(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_sanitize_artifact_name() {
+        assert_eq!(
+            sanitize_artifact_name("profile.0-m 128.zip").unwrap(),
+            "profile.0-m_128.zip"
+        );
+        assert_eq!(sanitize_artifact_name("../../.bashrc").unwrap(), "bashrc");
+        assert_eq!(
+            sanitize_artifact_name("..\\..\\evil.zip").unwrap(),
+            "evil.zip"
+        );
+        for name in ["", "..", "a/", "/"] {
+            assert!(sanitize_artifact_name(name).is_err(), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn test_unique_artifact_names_suffix_collisions() {
+        let names = unique_artifact_names([
+            "0/profile.zip",
+            "1/profile.zip",
+            "a b.zip",
+            "a_b.zip",
+            "other.zip",
+        ])
+        .unwrap();
+        assert_eq!(
+            names,
+            vec![
+                "profile.zip",
+                "profile-1.zip",
+                "a_b.zip",
+                "a_b-3.zip",
+                "other.zip"
+            ]
+        );
+        assert!(unique_artifact_names(["ok.zip", ".."]).is_err());
+    }
+
+    #[test]
+    fn test_extract_confines_traversing_entries() {
+        let dir = tempdir().unwrap();
+        let zip_path = dir.path().join("profile.zip");
+        write_zip(&zip_path, |zip| {
+            add_file(zip, "../../escape.ncu-rep", b"report");
+            add_file(zip, "/abs/ncu-details.txt", b"details");
+            add_file(zip, "ignored.bin", b"other");
+        });
+
+        let extracted = extract_profile_artifacts(&zip_path, ExtractLimits::default()).unwrap();
+
+        let extract_dir = dir.path().join("profile");
+        assert_eq!(extracted.reports, vec![extract_dir.join("escape.ncu-rep")]);
+        assert_eq!(extracted.details, vec![extract_dir.join("ncu-details.txt")]);
+        assert_eq!(
+            files_in(&extract_dir),
+            vec!["escape.ncu-rep", "ncu-details.txt"]
+        );
+        assert_eq!(files_in(dir.path()), vec!["profile", "profile.zip"]);
+    }
+
+    #[test]
+    fn test_extract_rejects_symlink_entries() {
+        let dir = tempdir().unwrap();
+        let zip_path = dir.path().join("profile.zip");
+        write_zip(&zip_path, |zip| {
+            zip.add_symlink(
+                "ncu-details.txt",
+                "/etc/passwd",
+                SimpleFileOptions::default(),
+            )
+            .unwrap();
+        });
+
+        let err = extract_profile_artifacts(&zip_path, ExtractLimits::default()).unwrap_err();
+
+        assert!(err.to_string().contains("symlink"), "{}", err);
+        assert!(files_in(&dir.path().join("profile")).is_empty());
+    }
+
+    #[test]
+    fn test_extract_caps_entries_and_uncompressed_size() {
+        let dir = tempdir().unwrap();
+        let zip_path = dir.path().join("profile.zip");
+        write_zip(&zip_path, |zip| {
+            add_file(zip, "a/ncu-details.txt", &[b'x'; 600]);
+            add_file(zip, "b/profile.ncu-rep", &[b'y'; 600]);
+        });
+
+        let too_many = ExtractLimits {
+            max_entries: 1,
+            ..ExtractLimits::default()
+        };
+        assert!(extract_profile_artifacts(&zip_path, too_many)
+            .unwrap_err()
+            .to_string()
+            .contains("entries"));
+
+        let too_big = ExtractLimits {
+            max_total_bytes: 1000,
+            ..ExtractLimits::default()
+        };
+        let err = extract_profile_artifacts(&zip_path, too_big).unwrap_err();
+        assert!(err.to_string().contains("byte limit"), "{}", err);
+        // The first file fit and was kept; the one that crossed the cap left
+        // neither a partial file nor its temporary copy behind.
+        assert_eq!(
+            files_in(&dir.path().join("profile")),
+            vec!["ncu-details.txt"]
+        );
+    }
+}
@@ -0,0 +1,396 @@
+use anyhow::{anyhow, Result};
+use futures_util::stream::{self, StreamExt, TryStreamExt};
+use reqwest::{header, Client, StatusCode};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::File as StdFile;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::fs::{self, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::time::{sleep, timeout};
+
+use super::events::{EventSink, SubmissionEvent};
+use super::retry::{self, RetryPolicy};
+
+/// Artifacts downloaded at the same time.
+const MAX_CONCURRENT_DOWNLOADS: usize = 3;
+/// A download that delivers no data for this long counts as interrupted.
+const CHUNK_TIMEOUT: Duration = Duration::from_secs(60);
+/// Overall cap on one download request. It replaces the client's short
+/// request timeout, which would cut off large reports; stalls are caught by
+/// `CHUNK_TIMEOUT` instead.
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(6 * 60 * 60);
+/// Download progress is reported at most once per this many bytes.
+const PROGRESS_STEP_BYTES: u64 = 16 * 1024 * 1024;
+
+/// A profiler artifact to fetch, with the size and SHA-256 the server sent
+/// for it, if any.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct ArtifactSource {
+    pub name: String,
+    pub url: String,
+    pub size: Option<u64>,
+    pub sha256: Option<String>,
+}
+
+impl ArtifactSource {
+    /// Read one entry of a profiler job's `artifacts` list. Relative URLs
+    /// resolve against `base_url`.
+    pub(super) fn from_json(artifact: &Value, base_url: &str) -> Result<Self> {
+        let name = artifact
+            .get("name")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("Profiler artifact missing name"))?;
+        let url = artifact
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("Profiler artifact missing url"))?;
+        let url = if url.starts_with("http://") || url.starts_with("https://") {
+            url.to_string()
+        } else {
+            format!("{}{}", base_url, url)
+        };
+        let size = ["size", "size_bytes"]
+            .iter()
+            .find_map(|key| artifact.get(*key).and_then(Value::as_u64));
+        // Accept a bare hex digest or the `sha256:<hex>` form.
+        let sha256 = ["sha256", "checksum"]
+            .iter()
+            .find_map(|key| artifact.get(*key).and_then(Value::as_str))
+            .map(|digest| digest.trim_start_matches("sha256:").to_lowercase())
+            .filter(|digest| !digest.is_empty());
+        Ok(Self {
+            name: name.to_string(),
+            url,
+            size,
+            sha256,
+        })
+    }
+}
+
+/// Download each artifact to its path, a few at a time. Fails on the first
+/// artifact that cannot be fetched or verified.
+pub(super) async fn download_all(
+    client: &Client,
+    downloads: &[(ArtifactSource, PathBuf)],
+    sink: &EventSink,
+) -> Result<()> {
+    stream::iter(downloads)
+        .map(|(source, dest)| download(client, source, dest, sink))
+        .buffer_unordered(MAX_CONCURRENT_DOWNLOADS)
+        .try_collect::<Vec<()>>()
+        .await?;
+    Ok(())
+}
+
+/// Why one download attempt stopped.
+enum Interruption {
+    /// The connection failed or stalled; the next attempt resumes.
+    Retry(String),
+    Fail(anyhow::Error),
+}
+
+impl Interruption {
+    fn stalled() -> Self {
+        Interruption::Retry(format!("no data for {}s", CHUNK_TIMEOUT.as_secs()))
+    }
+}
+
+/// Stream `source` into `<dest>.part`, resuming interrupted attempts with an
+/// HTTP Range request, then verify it and move it to `dest`.
+async fn download(
+    client: &Client,
+    source: &ArtifactSource,
+    dest: &Path,
+    sink: &EventSink,
+) -> Result<()> {
+    let part = partial_path(dest);
+    let policy = RetryPolicy::from_env();
+    let mut attempt = 0;
+    loop {
+        let before = partial_len(&part).await;
+        let result = fetch_into(client, source, &part, sink).await;
+        // Only attempts that got nowhere count against the limit, so a slow
+        // but moving download is never given up on.
+        if partial_len(&part).await > before {
+            attempt = 0;
+        }
+        match result {
+            Ok(()) => break,
+            Err(Interruption::Retry(reason)) if attempt < policy.max_retries => {
+                let delay = policy.backoff(attempt);
+                attempt += 1;
+                sink.notice(format!(
+                    "Download of {} interrupted ({}). Resuming in {:.1}s (attempt {}/{})...",
+                    source.name,
+                    reason,
+                    delay.as_secs_f64(),
+                    attempt,
+                    policy.max_retries
+                ));
+                sleep(delay).await;
+            }
+            Err(Interruption::Retry(reason)) => {
+                return Err(anyhow!(
+                    "Failed to download {} after {} attempts: {}",
+                    source.name,
+                    attempt + 1,
+                    reason
+                ))
+            }
+            Err(Interruption::Fail(e)) => return Err(e),
+        }
+    }
+
+    if let Err(e) = verify(&part, source) {
+        // A corrupt partial file would only be resumed again; start over
+        // next time.
+        let _ = fs::remove_file(&part).await;
+        return Err(e);
+    }
+    fs::rename(&part, dest)
+        .await
+        .map_err(|e| anyhow!("Failed to save {}: {}", dest.display(), e))
+}
+
+/// One attempt at fetching the rest of `source` into `part`.
+async fn fetch_into(
+    client: &Client,
+    source: &ArtifactSource,
+    part: &Path,
+    sink: &EventSink,
+) -> Result<(), Interruption> {
+    let mut offset = partial_len(part).await;
+    if let Some(size) = source.size {
+        if offset == size {
+            return Ok(());
+        }
+        if offset > size {
+            offset = 0;
+        }
+    }
+
+    let mut request = client.get(&source.url).timeout(DOWNLOAD_TIMEOUT);
+    if offset > 0 {
+        request = request.header(header::RANGE, format!("bytes={}-", offset));
+    }
+    let mut resp = timeout(CHUNK_TIMEOUT, request.send())
+        .await
+        .map_err(|_| Interruption::stalled())?
+        .map_err(|e| Interruption::Retry(e.to_string()))?;
+    let status = resp.status();
+    let append = match resume_action(status, offset) {
+        ResumeAction::Append => true,
+        ResumeAction::Restart => false,
+        ResumeAction::Complete => return Ok(()),
+        ResumeAction::Retry => {
+            return Err(Interruption::Retry(format!("server returned {}", status)))
+        }
+        ResumeAction::Fail => {
+            return Err(Interruption::Fail(anyhow!(
+                "Failed to download {}: server returned {}",
+                source.name,
+                status
+            )))
+        }
+    };
+
+    let fail = |e: std::io::Error| {
+        Interruption::Fail(anyhow!("Failed to write {}: {}", part.display(), e))
+    };
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(part)
+        .await
+        .map_err(fail)?;
+    let mut downloaded = if append { offset } else { 0 };
+    let total = resp
+        .content_length()
+        .map(|len| len + downloaded)
+        .or(source.size);
+    let mut reported = downloaded;
+    let progress = |downloaded| SubmissionEvent::DownloadProgress {
+        name: source.name.clone(),
+        downloaded,
+        total,
+    };
+
+    loop {
+        let chunk = timeout(CHUNK_TIMEOUT, resp.chunk())
+            .await
+            .map_err(|_| Interruption::stalled())?
+            .map_err(|e| Interruption::Retry(e.to_string()))?;
+        let Some(chunk) = chunk else {
+            break;
+        };
+        file.write_all(&chunk).await.map_err(fail)?;
+        downloaded += chunk.len() as u64;
+        if downloaded - reported >= PROGRESS_STEP_BYTES {
+            sink.emit(progress(downloaded));
+            reported = downloaded;
+        }
+    }
+    file.flush().await.map_err(fail)?;
+
+    if let Some(total) = total {
+        if downloaded < total {
+            return Err(Interruption::Retry(format!(
+                "connection closed after {} of {} bytes",
+                downloaded, total
+            )));
+        }
+    }
+    sink.emit(progress(downloaded));
+    Ok(())
+}
+
+#[derive(Debug, PartialEq)]
+enum ResumeAction {
+    /// The server sent the requested range: append it.
+    Append,
+    /// The server sent the whole file: overwrite what is there.
+    Restart,
+    /// Nothing left to fetch.
+    Complete,
+    Retry,
+    Fail,
+}
+
+/// What to do with a download response, given how many bytes were already
+/// on disk when it was requested.
+fn resume_action(status: StatusCode, offset: u64) -> ResumeAction {
+    match status {
+        StatusCode::PARTIAL_CONTENT if offset > 0 => ResumeAction::Append,
+        StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => ResumeAction::Complete,
+        status if status.is_success() => ResumeAction::Restart,
+        status if retry::is_retryable_status(status) => ResumeAction::Retry,
+        _ => ResumeAction::Fail,
+    }
+}
+
+/// Check a finished download against the size and checksum the server sent.
+fn verify(path: &Path, source: &ArtifactSource) -> Result<()> {
+    let len = std::fs::metadata(path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?
+        .len();
+    if let Some(size) = source.size {
+        if len != size {
+            return Err(anyhow!(
+                "Downloaded {} is {} bytes, expected {}",
+                source.name,
+                len,
+                size
+            ));
+        }
+    }
+    if let Some(expected) = &source.sha256 {
+        let mut file =
+            StdFile::open(path).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut file, &mut hasher)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        let actual = format!("{:x}", hasher.finalize());
+        if &actual != expected {
+            return Err(anyhow!(
+                "Checksum mismatch for {}: expected sha256 {}, got {}",
+                source.name,
+                expected,
+                actual
+            ));
+        }
+    }
+    Ok(())
+}
+
+async fn partial_len(part: &Path) -> u64 {
+    fs::metadata(part).await.map(|m| m.len()).unwrap_or(0)
+}
+
+fn partial_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_artifact_source_reads_integrity_fields() {
+        let source = ArtifactSource::from_json(
+            &json!({
+                "name": "profile.zip",
+                "url": "/jobs/1/artifacts/profile.zip",
+                "size_bytes": 42,
+                "checksum": "sha256:ABCD"
+            }),
+            "https://profiler.example",
+        )
+        .unwrap();
+        assert_eq!(
+            source,
+            ArtifactSource {
+                name: "profile.zip".to_string(),
+                url: "https://profiler.example/jobs/1/artifacts/profile.zip".to_string(),
+                size: Some(42),
+                sha256: Some("abcd".to_string()),
+            }
+        );
+        assert!(ArtifactSource::from_json(&json!({ "name": "a.zip" }), "").is_err());
+    }
+
+    #[test]
+    fn test_resume_action() {
+        assert_eq!(
+            resume_action(StatusCode::PARTIAL_CONTENT, 10),
+            ResumeAction::Append
+        );
+        assert_eq!(resume_action(StatusCode::OK, 10), ResumeAction::Restart);
+        assert_eq!(resume_action(StatusCode::OK, 0), ResumeAction::Restart);
+        assert_eq!(
+            resume_action(StatusCode::RANGE_NOT_SATISFIABLE, 10),
+            ResumeAction::Complete
+        );
+        assert_eq!(
+            resume_action(StatusCode::BAD_GATEWAY, 10),
+            ResumeAction::Retry
+        );
+        assert_eq!(resume_action(StatusCode::NOT_FOUND, 0), ResumeAction::Fail);
+    }
+
+    #[test]
+    fn test_verify_checks_size_and_sha256() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("profile.zip.part");
+        std::fs::write(&path, b"abc").unwrap();
+        let mut source = ArtifactSource {
+            name: "profile.zip".to_string(),
+            url: String::new(),
+            size: Some(3),
+            sha256: Some(
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_string(),
+            ),
+        };
+        verify(&path, &source).unwrap();
+
+        source.size = Some(4);
+        assert!(verify(&path, &source).is_err());
+        source.size = None;
+        source.sha256 = Some("00".to_string());
+        assert!(verify(&path, &source)
+            .unwrap_err()
+            .to_string()
+            .contains("Checksum mismatch"));
+        assert_eq!(
+            partial_path(Path::new("out/a.zip")),
+            Path::new("out/a.zip.part")
+        );
+    }
+}
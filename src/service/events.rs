@@ -0,0 +1,291 @@
+use anyhow::Result;
+use std::fmt;
+use std::path::PathBuf;
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::format_run_results;
+use super::queue::format_eta;
+use crate::models::RunResults;
+use crate::utils::format_bytes;
+
+/// Channel end the service reports submission progress on.
+pub type EventSender = UnboundedSender<SubmissionEvent>;
+
+/// One step in the life of a submission, emitted in the order it happens.
+/// Plain output, the TUI and machine-readable output all render the same
+/// events, so none of them has to parse log text.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SubmissionEvent {
+    /// The server took the upload. `id` is the submission id, or the job id
+    /// for Brev profiles.
+    Accepted {
+        id: String,
+    },
+    /// Jobs queued on the runner ahead of this one, with an ETA once the
+    /// queue has been seen to drain.
+    Queued {
+        runner: Option<String>,
+        position: i64,
+        eta_seconds: Option<u64>,
+    },
+    /// The job moved to a new status, e.g. `pending` or `running`.
+    StatusChanged {
+        status: String,
+    },
+    /// Output of a run; `run` is 1-based when the server reports several.
+    Stdout {
+        run: Option<usize>,
+        text: String,
+    },
+    Stderr {
+        run: Option<usize>,
+        text: String,
+    },
+    /// Structured test and benchmark results of one run.
+    BenchmarkResult {
+        run: usize,
+        results: RunResults,
+    },
+    /// A decoded profiler report for one benchmark shape.
+    ProfileReport {
+        profiler: String,
+        spec: String,
+        report: String,
+    },
+    /// A profiler output saved locally and/or offered for download.
+    ProfileArtifact {
+        path: Option<PathBuf>,
+        url: Option<String>,
+    },
+    /// Bytes of an artifact downloaded so far, out of `total` when known.
+    DownloadProgress {
+        name: String,
+        downloaded: u64,
+        total: Option<u64>,
+    },
+    /// Each run's stdout, stderr and raw result were saved under `dir`.
+    LogsSaved {
+        dir: PathBuf,
+        files: Vec<PathBuf>,
+    },
+    /// Anything else worth telling the user: retries, reconnects, warnings.
+    Notice(String),
+    /// The submission completed; the caller holds the full result.
+    Finished,
+    Failed {
+        error: String,
+    },
+}
+
+impl fmt::Display for SubmissionEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmissionEvent::Accepted { id } => {
+                write!(f, "Submission {} accepted. Waiting for results...", id)
+            }
+            SubmissionEvent::Queued {
+                runner,
+                position,
+                eta_seconds,
+            } => {
+                let jobs = if *position == 1 { "job" } else { "jobs" };
+                match runner {
+                    Some(runner) => write!(f, "{} queue: {} queued {}", runner, position, jobs)?,
+                    None => write!(f, "Queue position {}", position)?,
+                }
+                if let Some(eta) = eta_seconds {
+                    write!(f, ", ETA {}", format_eta(*eta))?;
+                }
+                Ok(())
+            }
+            SubmissionEvent::StatusChanged { status } => write!(f, "Status: {}", status),
+            SubmissionEvent::Stdout { run, text } => write_output(f, "STDOUT", *run, text),
+            SubmissionEvent::Stderr { run, text } => write_output(f, "STDERR", *run, text),
+            SubmissionEvent::BenchmarkResult { run, results } => write!(
+                f,
+                "RESULTS (Run {}):\n{}",
+                run,
+                format_run_results(results).unwrap_or_else(|| "No results reported".to_string())
+            ),
+            SubmissionEvent::ProfileReport {
+                profiler,
+                spec,
+                report,
+            } => write!(f, "=== {} report: {} ===\n{}", profiler, spec, report),
+            SubmissionEvent::ProfileArtifact { path, url } => {
+                let mut lines = Vec::new();
+                if let Some(path) = path {
+                    lines.push(format!("Saved profile trace to: {}", path.display()));
+                }
+                if let Some(url) = url {
+                    lines.push(format!("Download full profile: {}", url));
+                }
+                f.write_str(&lines.join("\n"))
+            }
+            SubmissionEvent::DownloadProgress {
+                name,
+                downloaded,
+                total,
+            } => match total {
+                Some(total) if *total > 0 => write!(
+                    f,
+                    "Downloading {}: {} / {} ({}%)",
+                    name,
+                    format_bytes(*downloaded),
+                    format_bytes(*total),
+                    downloaded * 100 / total
+                ),
+                _ => write!(f, "Downloading {}: {}", name, format_bytes(*downloaded)),
+            },
+            SubmissionEvent::LogsSaved { dir, files } => {
+                write!(f, "Run logs saved to {}", dir.display())?;
+                for file in files {
+                    write!(f, "\n  {}", file.display())?;
+                }
+                Ok(())
+            }
+            SubmissionEvent::Notice(message) => f.write_str(message),
+            SubmissionEvent::Finished => f.write_str("Submission finished."),
+            SubmissionEvent::Failed { error } => write!(f, "Submission failed: {}", error),
+        }
+    }
+}
+
+impl SubmissionEvent {
+    /// Text shown in progress output, or `None` for `Finished` and `Failed`,
+    /// which every front end reports from the submission's own result.
+    /// `elapsed_secs` stamps status changes.
+    pub fn progress_text(&self, elapsed_secs: u64) -> Option<String> {
+        match self {
+            SubmissionEvent::Finished | SubmissionEvent::Failed { .. } => None,
+            SubmissionEvent::StatusChanged { .. } => Some(format!("{} ({}s)", self, elapsed_secs)),
+            _ => Some(self.to_string()),
+        }
+    }
+}
+
+fn write_output(
+    f: &mut fmt::Formatter<'_>,
+    stream: &str,
+    run: Option<usize>,
+    text: &str,
+) -> fmt::Result {
+    match run {
+        Some(run) => write!(f, "{} (Run {}):\n{}", stream, run, text),
+        None => write!(f, "{}:\n{}", stream, text),
+    }
+}
+
+/// Where service functions emit events. Without a channel, or once the
+/// receiver is gone, events are dropped.
+#[derive(Clone, Debug, Default)]
+pub(super) struct EventSink(Option<EventSender>);
+
+impl EventSink {
+    pub(super) fn new(sender: Option<EventSender>) -> Self {
+        Self(sender)
+    }
+
+    pub(super) fn emit(&self, event: SubmissionEvent) {
+        if let Some(sender) = &self.0 {
+            let _ = sender.send(event);
+        }
+    }
+
+    pub(super) fn notice(&self, message: String) {
+        self.emit(SubmissionEvent::Notice(message));
+    }
+
+    /// Close out a submission with `Finished` or `Failed`.
+    pub(super) fn finish<T>(&self, result: &Result<T>) {
+        self.emit(match result {
+            Ok(_) => SubmissionEvent::Finished,
+            Err(e) => SubmissionEvent::Failed {
+                error: e.to_string(),
+            },
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TestResult;
+
+    #[test]
+    fn test_event_rendering() {
+        let queued = SubmissionEvent::Queued {
+            runner: Some("Modal B200".to_string()),
+            position: 1,
+            eta_seconds: Some(80),
+        };
+        assert_eq!(
+            queued.to_string(),
+            "Modal B200 queue: 1 queued job, ETA ~1m 20s"
+        );
+        let stdout = SubmissionEvent::Stdout {
+            run: Some(2),
+            text: "ok".to_string(),
+        };
+        assert_eq!(stdout.to_string(), "STDOUT (Run 2):\nok");
+        let artifact = SubmissionEvent::ProfileArtifact {
+            path: Some(PathBuf::from("trace.zip")),
+            url: Some("https://example.com/p".to_string()),
+        };
+        assert_eq!(
+            artifact.to_string(),
+            "Saved profile trace to: trace.zip\nDownload full profile: https://example.com/p"
+        );
+        let results = SubmissionEvent::BenchmarkResult {
+            run: 1,
+            results: RunResults {
+                tests: vec![TestResult {
+                    spec: "k: 128".to_string(),
+                    status: "pass".to_string(),
+                    passed: true,
+                    ..Default::default()
+                }],
+                benchmarks: Vec::new(),
+            },
+        };
+        assert!(results.to_string().starts_with("RESULTS (Run 1):\n"));
+        assert!(results.to_string().contains("k: 128"));
+        let download = SubmissionEvent::DownloadProgress {
+            name: "profile.zip".to_string(),
+            downloaded: 16 << 20,
+            total: Some(64 << 20),
+        };
+        assert_eq!(
+            download.to_string(),
+            "Downloading profile.zip: 16.0 MiB / 64.0 MiB (25%)"
+        );
+    }
+
+    #[test]
+    fn test_progress_text_stamps_status_and_skips_outcome() {
+        let status = SubmissionEvent::StatusChanged {
+            status: "running".to_string(),
+        };
+        assert_eq!(
+            status.progress_text(15).as_deref(),
+            Some("Status: running (15s)")
+        );
+        assert_eq!(SubmissionEvent::Finished.progress_text(3), None);
+    }
+
+    #[test]
+    fn test_sink_reports_outcome_and_tolerates_closed_channel() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let sink = EventSink::new(Some(tx));
+        sink.finish(&Err::<(), _>(anyhow::anyhow!("boom")));
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            SubmissionEvent::Failed {
+                error: "boom".to_string()
+            }
+        );
+        drop(rx);
+        sink.finish(&Ok(()));
+        EventSink::default().notice("nobody listening".to_string());
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::{anyhow, Result};
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+use std::env;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::response_error_text;
+
+/// A job accepted by a remote service that the CLI is still waiting on.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RemoteJob {
+    Submission(i64),
+    BrevProfile { base_url: String, job_id: String },
+}
+
+impl fmt::Display for RemoteJob {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteJob::Submission(id) => write!(f, "submission {}", id),
+            RemoteJob::BrevProfile { job_id, .. } => write!(f, "profile job {}", job_id),
+        }
+    }
+}
+
+/// Jobs currently being waited on, so an interrupt handler can offer to
+/// cancel them without the submit functions having to hand their ids out.
+static ACTIVE_JOBS: Mutex<Vec<RemoteJob>> = Mutex::new(Vec::new());
+
+/// Keeps a job in the active list until dropped, which also covers a waiting
+/// task that is aborted rather than returning.
+pub(super) struct ActiveJobGuard(RemoteJob);
+
+impl ActiveJobGuard {
+    pub(super) fn register(job: RemoteJob) -> Self {
+        if let Ok(mut jobs) = ACTIVE_JOBS.lock() {
+            jobs.push(job.clone());
+        }
+        Self(job)
+    }
+}
+
+impl Drop for ActiveJobGuard {
+    fn drop(&mut self) {
+        if let Ok(mut jobs) = ACTIVE_JOBS.lock() {
+            if let Some(index) = jobs.iter().position(|job| *job == self.0) {
+                jobs.remove(index);
+            }
+        }
+    }
+}
+
+/// Snapshot of the jobs the CLI is currently waiting on.
+pub fn active_jobs() -> Vec<RemoteJob> {
+    ACTIVE_JOBS
+        .lock()
+        .map(|jobs| jobs.clone())
+        .unwrap_or_default()
+}
+
+/// Whether a cancel request took effect, with the server's explanation.
+#[derive(Debug)]
+pub struct CancelOutcome {
+    /// `None` when the server accepted the request without saying whether
+    /// the job was actually stopped.
+    pub cancelled: Option<bool>,
+    pub message: Option<String>,
+}
+
+pub async fn cancel_remote_job(client: &Client, job: &RemoteJob) -> Result<CancelOutcome> {
+    let url = match job {
+        RemoteJob::Submission(id) => {
+            let base_url =
+                env::var("POPCORN_API_URL").map_err(|_| anyhow!("POPCORN_API_URL is not set"))?;
+            format!("{}/user/submissions/{}/cancel", base_url, id)
+        }
+        RemoteJob::BrevProfile { base_url, job_id } => {
+            format!("{}/jobs/{}/cancel", base_url, job_id)
+        }
+    };
+
+    let resp = client
+        .post(url)
+        .timeout(Duration::from_secs(30))
+        .send()
+        .await?;
+
+    let status = resp.status();
+    // The job already finished (or never existed): nothing was cancelled.
+    if status == StatusCode::NOT_FOUND || status == StatusCode::CONFLICT {
+        return Ok(CancelOutcome {
+            cancelled: Some(false),
+            message: Some(response_error_text(resp).await?),
+        });
+    }
+    if !status.is_success() {
+        return Err(anyhow!(
+            "Server returned status {}: {}",
+            status,
+            response_error_text(resp).await?
+        ));
+    }
+
+    let body: Value = resp.json().await.unwrap_or(Value::Null);
+    Ok(cancel_outcome(&body))
+}
+
+/// Only an explicit `cancelled` flag, or a `cancelled` status, counts as an
+/// answer; a bare success status does not confirm the job stopped.
+fn cancel_outcome(body: &Value) -> CancelOutcome {
+    let status = body.get("status").and_then(|v| v.as_str());
+    CancelOutcome {
+        cancelled: body.get("cancelled").and_then(|v| v.as_bool()).or_else(|| {
+            status
+                .filter(|s| s.eq_ignore_ascii_case("cancelled"))
+                .map(|_| true)
+        }),
+        message: body
+            .get("detail")
+            .or_else(|| body.get("status"))
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_active_job_guard_unregisters_on_drop() {
+        let job = RemoteJob::Submission(987_654);
+        {
+            let _guard = ActiveJobGuard::register(job.clone());
+            assert!(active_jobs().contains(&job));
+        }
+        assert!(!active_jobs().contains(&job));
+    }
+
+    #[test]
+    fn test_cancel_outcome_needs_confirmation() {
+        let outcome = cancel_outcome(&serde_json::json!({ "status": "cancelled" }));
+        assert_eq!(outcome.cancelled, Some(true));
+        assert_eq!(outcome.message.as_deref(), Some("cancelled"));
+
+        let outcome = cancel_outcome(&serde_json::json!({
+            "cancelled": false,
+            "detail": "job is already running on the GPU"
+        }));
+        assert_eq!(outcome.cancelled, Some(false));
+
+        assert_eq!(cancel_outcome(&Value::Null).cancelled, None);
+        let outcome = cancel_outcome(&serde_json::json!({ "status": "accepted" }));
+        assert_eq!(outcome.cancelled, None);
+    }
+}
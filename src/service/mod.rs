@@ -3,22 +3,51 @@ use base64::Engine;
 use chrono::{DateTime, Utc};
 use reqwest::header::{HeaderMap, HeaderValue};
 use reqwest::multipart::{Form, Part};
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use serde_json::Value;
 use std::env;
-use std::fs::File as StdFile;
-use std::io::Cursor;
+use std::future::Future;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
-use tokio::io::AsyncWriteExt;
+use std::time::{Duration, Instant};
 use tokio::time::sleep;
-use zip::ZipArchive;
 
+use crate::history::flagged::{self, FlaggedVerdict};
+use crate::history::runs::{self, RunLog};
+use crate::history::{self, SubmissionRecord};
 use crate::models::{
-    GpuItem, LeaderboardItem, RunnerQueueStatus, SubmissionDetails, SubmissionJobStatus,
-    SubmissionRun, UserSubmission, UserSubmissionRun,
+    BenchmarkResult, GpuItem, LeaderboardItem, RunResults, RunnerQueueStatus, SubmissionDetails,
+    SubmissionJobStatus, SubmissionMode, SubmissionRun, SupportedModes, TestResult, UserSubmission,
+    UserSubmissionRun,
 };
-
+use crate::utils;
+
+mod artifacts;
+mod download;
+mod events;
+mod jobs;
+mod queue;
+mod retry;
+mod sse;
+mod verdict;
+
+use artifacts::ExtractLimits;
+use download::ArtifactSource;
+use events::EventSink;
+pub use events::{EventSender, SubmissionEvent};
+use jobs::ActiveJobGuard;
+pub use jobs::{active_jobs, cancel_remote_job, RemoteJob};
+use queue::QueueTracker;
+pub use queue::{fetch_runner_queue, wait_for_queue_below};
+use retry::LogCallback;
+use sse::{SseDecoder, SseEvent};
+
+/// Sent when reconnecting to an event stream so the server resumes after
+/// the last event the CLI saw.
+const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+/// An event stream connection that delivered events and stayed up this long
+/// was healthy: the next drop starts a fresh run of reconnect attempts.
+const STREAM_HEALTHY_AFTER: Duration = Duration::from_secs(60);
 const SUBMISSION_POLL_INTERVAL_SECONDS: u64 = 5;
 const SUBMISSION_POLL_TIMEOUT_SECONDS: u64 = 60 * 60;
 
@@ -34,6 +63,257 @@ fn parse_score(value: &Value) -> Option<f64> {
     }
 }
 
+/// A run's stdout or stderr, found either on the run itself or under its
+/// `meta` object depending on the server version. Empty output counts as none.
+fn run_output(run: &Value, stream: &str) -> Option<String> {
+    run.get(stream)
+        .and_then(non_empty_str)
+        .or_else(|| {
+            run.get("meta")
+                .and_then(|meta| meta.get(stream))
+                .and_then(non_empty_str)
+        })
+        .map(str::to_string)
+}
+
+/// Parse the structured test and benchmark results from a run's `result`
+/// object. The runner reports them as flat keys — `test-count` and
+/// `test.{i}.spec|status|error`, `benchmark-count` and
+/// `benchmark.{i}.spec|mean|std|best|worst|runs` — with numbers often sent as
+/// JSON strings.
+pub fn parse_run_results(result: &Value) -> RunResults {
+    let Some(result) = result.as_object() else {
+        return RunResults::default();
+    };
+    let count = |key: &str| {
+        result
+            .get(key)
+            .and_then(parse_score)
+            .map(|c| c.max(0.0) as usize)
+            .unwrap_or(0)
+    };
+    let text = |key: String| {
+        result
+            .get(&key)
+            .and_then(|s| s.as_str())
+            .map(str::to_string)
+    };
+    let number = |key: String| result.get(&key).and_then(parse_score);
+
+    let tests = (0..count("test-count"))
+        .map(|i| {
+            let status = text(format!("test.{}.status", i)).unwrap_or_default();
+            let error = text(format!("test.{}.error", i)).filter(|e| !e.is_empty());
+            // Prefer dedicated keys; older checkers only mention the
+            // deviation in the error message.
+            let stat = |key: &str, names: &[&str]| {
+                number(format!("test.{}.{}", i, key))
+                    .or_else(|| error.as_deref().and_then(|e| error_stat(e, names)))
+            };
+            TestResult {
+                spec: text(format!("test.{}.spec", i)).unwrap_or_else(|| "unknown".to_string()),
+                passed: status == "pass",
+                max_abs_error: stat(
+                    "max_abs_error",
+                    &["max abs error", "max_abs_error", "max absolute error"],
+                ),
+                max_rel_error: stat(
+                    "max_rel_error",
+                    &["max rel error", "max_rel_error", "max relative error"],
+                ),
+                status,
+                error,
+            }
+        })
+        .collect();
+
+    let benchmarks = (0..count("benchmark-count"))
+        .map(|i| BenchmarkResult {
+            spec: text(format!("benchmark.{}.spec", i)).unwrap_or_else(|| "unknown".to_string()),
+            mean: number(format!("benchmark.{}.mean", i)),
+            std: number(format!("benchmark.{}.std", i)),
+            min: number(format!("benchmark.{}.best", i)),
+            max: number(format!("benchmark.{}.worst", i)),
+            runs: number(format!("benchmark.{}.runs", i)).map(|r| r as u64),
+        })
+        .collect();
+
+    RunResults { tests, benchmarks }
+}
+
+/// Render per-shape benchmark statistics as an aligned plain-text table.
+pub fn format_benchmark_table(benchmarks: &[BenchmarkResult]) -> String {
+    let time = |t: Option<f64>| {
+        t.map(utils::format_duration_ns)
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let spec_width = benchmarks
+        .iter()
+        .map(|b| b.spec.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Benchmark".len());
+
+    let mut lines = vec![format!(
+        "{:<w$}  {:>11}  {:>11}  {:>11}  {:>11}  {:>6}",
+        "Benchmark",
+        "Mean",
+        "Std",
+        "Min",
+        "Max",
+        "Runs",
+        w = spec_width
+    )];
+    for b in benchmarks {
+        lines.push(format!(
+            "{:<w$}  {:>11}  {:>11}  {:>11}  {:>11}  {:>6}",
+            b.spec,
+            time(b.mean),
+            time(b.std),
+            time(b.min),
+            time(b.max),
+            b.runs
+                .map(|r| r.to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            w = spec_width
+        ));
+    }
+    lines.join("\n")
+}
+
+/// The number following one of `names` in a checker error message, e.g.
+/// `0.031` in "mismatch: max abs error: 0.031". Names match case-insensitively.
+fn error_stat(error: &str, names: &[&str]) -> Option<f64> {
+    let lower = error.to_lowercase();
+    names.iter().find_map(|name| {
+        let start = lower.find(name)? + name.len();
+        let rest = lower[start..].trim_start_matches([':', '=', ' ']);
+        let end = rest
+            .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | 'e' | '-' | '+')))
+            .unwrap_or(rest.len());
+        rest[..end].trim_end_matches('.').parse().ok()
+    })
+}
+
+/// Marks the first failing test in the failure table; the TUI highlights
+/// lines starting with it.
+pub const FIRST_FAILURE_MARKER: char = '▶';
+
+/// Longest error excerpt shown in the failure table.
+const FAILURE_ERROR_CHARS: usize = 80;
+
+/// Render test outcomes as an aligned plain-text table.
+pub fn format_test_table(tests: &[TestResult]) -> String {
+    let spec_width = tests
+        .iter()
+        .map(|t| t.spec.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Test".len());
+
+    let mut lines = vec![format!("{:<w$}  Status", "Test", w = spec_width)];
+    for t in tests {
+        lines.push(format!(
+            "{:<w$}  {}",
+            t.spec,
+            if t.passed { "pass" } else { "FAIL" },
+            w = spec_width
+        ));
+    }
+    lines.join("\n")
+}
+
+/// Render the failing tests as a compact table — status, deviation from the
+/// reference and the first line of the error — with the first failure
+/// marked. Errors the table cuts short follow it in full, so multi-line
+/// checker output is kept. `None` if every test passed.
+pub fn format_failure_table(tests: &[TestResult]) -> Option<String> {
+    let failures: Vec<&TestResult> = tests.iter().filter(|t| !t.passed).collect();
+    if failures.is_empty() {
+        return None;
+    }
+    let stat = |v: Option<f64>| v.map_or_else(|| "-".to_string(), |v| format!("{:.3e}", v));
+    let error = |t: &TestResult| {
+        let line = t
+            .error
+            .as_deref()
+            .and_then(|e| e.lines().next())
+            .unwrap_or("-");
+        if line.chars().count() > FAILURE_ERROR_CHARS {
+            let cut: String = line.chars().take(FAILURE_ERROR_CHARS - 3).collect();
+            format!("{}...", cut)
+        } else {
+            line.to_string()
+        }
+    };
+    let spec_width = failures
+        .iter()
+        .map(|t| t.spec.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Test".len());
+    let status_width = failures
+        .iter()
+        .map(|t| t.status.chars().count().max(1))
+        .max()
+        .unwrap_or(0)
+        .max("Status".len());
+
+    let mut lines = vec![
+        format!("Failures ({} of {} tests):", failures.len(), tests.len()),
+        format!(
+            "  {:<w$}  {:<s$}  {:>11}  {:>11}  Error",
+            "Test",
+            "Status",
+            "Max abs err",
+            "Max rel err",
+            w = spec_width,
+            s = status_width
+        ),
+    ];
+    for (i, t) in failures.iter().enumerate() {
+        let marker = if i == 0 { FIRST_FAILURE_MARKER } else { ' ' };
+        let status = if t.status.is_empty() { "-" } else { &t.status };
+        lines.push(format!(
+            "{} {:<w$}  {:<s$}  {:>11}  {:>11}  {}",
+            marker,
+            t.spec,
+            status,
+            stat(t.max_abs_error),
+            stat(t.max_rel_error),
+            error(t),
+            w = spec_width,
+            s = status_width
+        ));
+    }
+    for t in &failures {
+        let full = t.error.as_deref().map(str::trim_end);
+        if let Some(full) = full.filter(|full| *full != error(t)) {
+            lines.push(String::new());
+            lines.push(format!("Error for {}:", t.spec));
+            lines.extend(full.lines().map(|line| format!("  {}", line)));
+        }
+    }
+    Some(lines.join("\n"))
+}
+
+/// Tables for a run's structured results, or `None` if it reported none.
+fn format_run_results(results: &RunResults) -> Option<String> {
+    let mut sections = Vec::new();
+    if !results.tests.is_empty() {
+        sections.push(format_test_table(&results.tests));
+        sections.extend(format_failure_table(&results.tests));
+    }
+    if !results.benchmarks.is_empty() {
+        sections.push(format_benchmark_table(&results.benchmarks));
+    }
+    if sections.is_empty() {
+        None
+    } else {
+        Some(sections.join("\n\n"))
+    }
+}
+
 fn parse_runner_queue(value: Option<&Value>) -> Option<RunnerQueueStatus> {
     let queue = value?;
     if queue.is_null() {
@@ -53,20 +333,21 @@ fn parse_runner_queue(value: Option<&Value>) -> Option<RunnerQueueStatus> {
     })
 }
 
-fn runner_queue_summary(queue: Option<&RunnerQueueStatus>) -> Option<String> {
+/// The `Queued` event for a runner queue, or `None` if the server did not
+/// report its length.
+fn queue_event(
+    queue: Option<&RunnerQueueStatus>,
+    eta_seconds: Option<u64>,
+) -> Option<SubmissionEvent> {
     let queue = queue?;
-    let queued_jobs = queue.queued_jobs?;
+    let position = queue.queued_jobs?;
     let runner = queue.runner.as_deref().unwrap_or("Runner");
     let gpu = queue.gpu.as_deref().unwrap_or("selected GPU");
-    let suffix = match queued_jobs {
-        1 => "job",
-        _ => "jobs",
-    };
-
-    Some(format!(
-        "{} {} queue: {} queued {}",
-        runner, gpu, queued_jobs, suffix
-    ))
+    Some(SubmissionEvent::Queued {
+        runner: Some(format!("{} {}", runner, gpu)),
+        position,
+        eta_seconds,
+    })
 }
 
 // Helper function to create a reusable reqwest client
@@ -153,11 +434,7 @@ pub async fn admin_stats(client: &Client, last_day_only: bool) -> Result<Value>
         format!("{}/admin/stats", base_url)
     };
 
-    let resp = client
-        .get(url)
-        .timeout(Duration::from_secs(30))
-        .send()
-        .await?;
+    let resp = retry::send(client.get(url).timeout(Duration::from_secs(30)), None).await?;
 
     handle_admin_response(resp).await
 }
@@ -167,11 +444,13 @@ pub async fn admin_get_submission(client: &Client, submission_id: i64) -> Result
     let base_url =
         env::var("POPCORN_API_URL").map_err(|_| anyhow!("POPCORN_API_URL is not set"))?;
 
-    let resp = client
-        .get(format!("{}/admin/submissions/{}", base_url, submission_id))
-        .timeout(Duration::from_secs(30))
-        .send()
-        .await?;
+    let resp = retry::send(
+        client
+            .get(format!("{}/admin/submissions/{}", base_url, submission_id))
+            .timeout(Duration::from_secs(30)),
+        None,
+    )
+    .await?;
 
     handle_admin_response(resp).await
 }
@@ -301,14 +580,16 @@ pub async fn admin_list_invites(client: &Client, leaderboard_name: &str) -> Resu
     let base_url =
         env::var("POPCORN_API_URL").map_err(|_| anyhow!("POPCORN_API_URL is not set"))?;
 
-    let resp = client
-        .get(format!(
-            "{}/admin/leaderboards/{}/invites",
-            base_url, leaderboard_name
-        ))
-        .timeout(Duration::from_secs(30))
-        .send()
-        .await?;
+    let resp = retry::send(
+        client
+            .get(format!(
+                "{}/admin/leaderboards/{}/invites",
+                base_url, leaderboard_name
+            ))
+            .timeout(Duration::from_secs(30)),
+        None,
+    )
+    .await?;
 
     handle_admin_response(resp).await
 }
@@ -350,11 +631,13 @@ pub async fn fetch_leaderboards(client: &Client) -> Result<Vec<LeaderboardItem>>
     let base_url =
         env::var("POPCORN_API_URL").map_err(|_| anyhow!("POPCORN_API_URL is not set"))?;
 
-    let resp = client
-        .get(format!("{}/leaderboards", base_url))
-        .timeout(Duration::from_secs(30))
-        .send()
-        .await?;
+    let resp = retry::send(
+        client
+            .get(format!("{}/leaderboards", base_url))
+            .timeout(Duration::from_secs(30)),
+        None,
+    )
+    .await?;
 
     let status = resp.status();
     if !status.is_success() {
@@ -376,9 +659,15 @@ pub async fn fetch_leaderboards(client: &Client) -> Result<Vec<LeaderboardItem>>
             .as_str()
             .ok_or_else(|| anyhow!("Invalid JSON structure"))?;
 
+        let deadline = lb["deadline"]
+            .as_str()
+            .filter(|d| !d.trim().is_empty())
+            .map(str::to_string);
+
         leaderboard_items.push(LeaderboardItem::new(
             name.to_string(),
             description.to_string(),
+            deadline,
         ));
     }
 
@@ -389,11 +678,13 @@ pub async fn fetch_gpus(client: &Client, leaderboard: &str) -> Result<Vec<GpuIte
     let base_url =
         env::var("POPCORN_API_URL").map_err(|_| anyhow!("POPCORN_API_URL is not set"))?;
 
-    let resp = client
-        .get(format!("{}/gpus/{}", base_url, leaderboard))
-        .timeout(Duration::from_secs(120))
-        .send()
-        .await?;
+    let resp = retry::send(
+        client
+            .get(format!("{}/gpus/{}", base_url, leaderboard))
+            .timeout(Duration::from_secs(120)),
+        None,
+    )
+    .await?;
 
     let status = resp.status();
     if !status.is_success() {
@@ -408,6 +699,69 @@ pub async fn fetch_gpus(client: &Client, leaderboard: &str) -> Result<Vec<GpuIte
     Ok(gpu_items)
 }
 
+/// Modes and profilers the server accepts for a leaderboard's GPU, or `None`
+/// if it does not report them (every mode is then offered).
+pub async fn fetch_supported_modes(
+    client: &Client,
+    leaderboard: &str,
+    gpu: &str,
+) -> Result<Option<SupportedModes>> {
+    let base_url =
+        env::var("POPCORN_API_URL").map_err(|_| anyhow!("POPCORN_API_URL is not set"))?;
+
+    let resp = retry::send(
+        client
+            .get(format!(
+                "{}/modes/{}/{}",
+                base_url,
+                leaderboard.to_lowercase(),
+                gpu
+            ))
+            .timeout(Duration::from_secs(30)),
+        None,
+    )
+    .await?;
+
+    let status = resp.status();
+    if status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        let error_text = resp.text().await?;
+        return Err(anyhow!("Server returned status {}: {}", status, error_text));
+    }
+
+    let body: Value = resp.json().await?;
+    Ok(Some(parse_supported_modes(&body)))
+}
+
+/// Modes the CLI does not know are skipped rather than rejected.
+fn parse_supported_modes(body: &Value) -> SupportedModes {
+    let strings = |key: &str| -> Vec<String> {
+        body.get(key)
+            .and_then(|v| v.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|v| v.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    let mut modes = Vec::new();
+    for mode in strings("modes")
+        .iter()
+        .filter_map(|m| SubmissionMode::parse(m))
+    {
+        if !modes.contains(&mode) {
+            modes.push(mode);
+        }
+    }
+    SupportedModes {
+        modes,
+        profilers: strings("profilers"),
+    }
+}
+
 /// Get the authenticated user's submissions
 pub async fn get_user_submissions(
     client: &Client,
@@ -429,11 +783,7 @@ pub async fn get_user_submissions(
         url = format!("{}?{}", url, params.join("&"));
     }
 
-    let resp = client
-        .get(&url)
-        .timeout(Duration::from_secs(30))
-        .send()
-        .await?;
+    let resp = retry::send(client.get(&url).timeout(Duration::from_secs(30)), None).await?;
 
     let status = resp.status();
     if !status.is_success() {
@@ -479,15 +829,29 @@ pub async fn get_user_submissions(
 
 /// Get a specific submission by ID (with code)
 pub async fn get_user_submission(client: &Client, submission_id: i64) -> Result<SubmissionDetails> {
+    fetch_user_submission(client, submission_id, None).await
+}
+
+async fn fetch_user_submission(
+    client: &Client,
+    submission_id: i64,
+    on_log: Option<&LogCallback<'_>>,
+) -> Result<SubmissionDetails> {
     let base_url =
         env::var("POPCORN_API_URL").map_err(|_| anyhow!("POPCORN_API_URL is not set"))?;
 
-    let resp = client
-        .get(format!("{}/user/submissions/{}", base_url, submission_id))
-        .timeout(Duration::from_secs(30))
-        .send()
-        .await?;
+    let resp = retry::send(
+        client
+            .get(format!("{}/user/submissions/{}", base_url, submission_id))
+            .timeout(Duration::from_secs(30)),
+        on_log,
+    )
+    .await?;
+    submission_details_from_response(resp).await
+}
 
+/// Parse a `/user/submissions/{id}` response into its details.
+async fn submission_details_from_response(resp: reqwest::Response) -> Result<SubmissionDetails> {
     let status = resp.status();
     if !status.is_success() {
         let error_text = resp.text().await?;
@@ -507,14 +871,22 @@ pub async fn get_user_submission(client: &Client, submission_id: i64) -> Result<
         .as_array()
         .map(|arr| {
             arr.iter()
-                .map(|r| SubmissionRun {
-                    start_time: r["start_time"].as_str().map(str::to_string),
-                    end_time: r["end_time"].as_str().map(str::to_string),
-                    mode: r["mode"].as_str().unwrap_or("").to_string(),
-                    secret: r["secret"].as_bool().unwrap_or(false),
-                    runner: r["runner"].as_str().unwrap_or("").to_string(),
-                    score: parse_score(&r["score"]),
-                    passed: r["passed"].as_bool().unwrap_or(false),
+                .map(|r| {
+                    let results = parse_run_results(&r["result"]);
+                    SubmissionRun {
+                        start_time: r["start_time"].as_str().map(str::to_string),
+                        end_time: r["end_time"].as_str().map(str::to_string),
+                        mode: r["mode"].as_str().unwrap_or("").to_string(),
+                        secret: r["secret"].as_bool().unwrap_or(false),
+                        runner: r["runner"].as_str().unwrap_or("").to_string(),
+                        score: parse_score(&r["score"]),
+                        passed: r["passed"].as_bool().unwrap_or(false),
+                        tests: results.tests,
+                        benchmarks: results.benchmarks,
+                        stdout: run_output(r, "stdout"),
+                        stderr: run_output(r, "stderr"),
+                        raw_result: r["result"].clone(),
+                    }
                 })
                 .collect()
         })
@@ -527,6 +899,7 @@ pub async fn get_user_submission(client: &Client, submission_id: i64) -> Result<
             Some(SubmissionJobStatus {
                 status: job["status"].as_str().map(str::to_string),
                 error: job["error"].as_str().map(str::to_string),
+                findings: verdict::parse_hack_findings(job),
             })
         }
     });
@@ -613,41 +986,67 @@ pub async fn submit_solution<P: AsRef<Path>>(
     file_content: &[u8],
     leaderboard: &str,
     gpu: &str,
-    submission_mode: &str,
-    on_log: Option<Box<dyn Fn(String) + Send + Sync>>,
+    submission_mode: SubmissionMode,
+    events: Option<EventSender>,
 ) -> Result<String> {
-    if submission_mode.eq_ignore_ascii_case("profile") {
-        return submit_solution_streaming(
+    let sink = EventSink::new(events);
+    let result = if submission_mode == SubmissionMode::Profile {
+        submit_solution_streaming(
             client,
             filepath,
             file_content,
             leaderboard,
             gpu,
             submission_mode,
-            on_log,
+            &sink,
         )
-        .await;
-    }
+        .await
+    } else {
+        run_submission_background(
+            client,
+            filepath,
+            file_content,
+            leaderboard,
+            gpu,
+            submission_mode,
+            &sink,
+        )
+        .await
+        .and_then(|details| format_submission_details(&details))
+    };
+    sink.finish(&result);
+    result
+}
 
-    submit_solution_background(
+pub async fn profile_brev_solution<P: AsRef<Path>>(
+    client: &Client,
+    filepath: P,
+    file_content: &[u8],
+    leaderboard: &str,
+    benchmark_index: Option<usize>,
+    events: Option<EventSender>,
+) -> Result<String> {
+    let sink = EventSink::new(events);
+    let result = run_brev_profile(
         client,
         filepath,
         file_content,
         leaderboard,
-        gpu,
-        submission_mode,
-        on_log,
+        benchmark_index,
+        &sink,
     )
-    .await
+    .await;
+    sink.finish(&result);
+    result
 }
 
-pub async fn profile_brev_solution<P: AsRef<Path>>(
+async fn run_brev_profile<P: AsRef<Path>>(
     client: &Client,
     filepath: P,
     file_content: &[u8],
     leaderboard: &str,
     benchmark_index: Option<usize>,
-    on_log: Option<Box<dyn Fn(String) + Send + Sync>>,
+    sink: &EventSink,
 ) -> Result<String> {
     let base_url = env::var("POPCORN_BREV_PROFILER_URL")
         .or_else(|_| env::var("BREV_PROFILER_URL"))
@@ -695,37 +1094,42 @@ pub async fn profile_brev_solution<P: AsRef<Path>>(
         .ok_or_else(|| anyhow!("Profiler did not return a job_id"))?
         .to_string();
 
-    if let Some(ref cb) = on_log {
-        cb(format!(
-            "Profile job {} accepted. Waiting for results...",
-            job_id
-        ));
-    }
+    let _active = ActiveJobGuard::register(RemoteJob::BrevProfile {
+        base_url: base_url.to_string(),
+        job_id: job_id.clone(),
+    });
+
+    sink.emit(SubmissionEvent::Accepted { id: job_id.clone() });
 
+    let log = |msg: String| sink.notice(msg);
+    let mut last_status = None;
+    let mut last_position = None;
     let mut elapsed = 0;
     loop {
-        let resp = match client
-            .get(format!("{}/jobs/{}", base_url, job_id))
-            .timeout(Duration::from_secs(30))
-            .send()
-            .await
-        {
-            Ok(resp) => resp,
-            Err(err) => {
-                if elapsed >= SUBMISSION_POLL_TIMEOUT_SECONDS {
-                    return Err(err.into());
-                }
-                if let Some(ref cb) = on_log {
-                    cb(format!(
-                        "Profile job {} status poll failed: {}. Retrying...",
-                        job_id, err
-                    ));
-                }
-                sleep(Duration::from_secs(SUBMISSION_POLL_INTERVAL_SECONDS)).await;
-                elapsed += SUBMISSION_POLL_INTERVAL_SECONDS;
-                continue;
+        let polled = retry::send(
+            client
+                .get(format!("{}/jobs/{}", base_url, job_id))
+                .timeout(Duration::from_secs(30)),
+            Some(&log),
+        )
+        .await;
+        if let Some(failure) = transient_poll_failure(&polled) {
+            if elapsed >= SUBMISSION_POLL_TIMEOUT_SECONDS {
+                return Err(anyhow!(
+                    "Failed to poll profile job {}: {}",
+                    job_id,
+                    failure
+                ));
             }
-        };
+            sink.notice(format!(
+                "Polling profile job {} failed ({}). Trying again in {}s...",
+                job_id, failure, SUBMISSION_POLL_INTERVAL_SECONDS
+            ));
+            sleep(Duration::from_secs(SUBMISSION_POLL_INTERVAL_SECONDS)).await;
+            elapsed += SUBMISSION_POLL_INTERVAL_SECONDS;
+            continue;
+        }
+        let resp = polled?;
 
         let status = resp.status();
         if !status.is_success() {
@@ -743,22 +1147,43 @@ pub async fn profile_brev_solution<P: AsRef<Path>>(
             .unwrap_or("unknown");
         let queue_position = job.get("queue_position").and_then(|v| v.as_i64());
 
-        if let Some(ref cb) = on_log {
-            match queue_position {
-                Some(pos) => cb(format!(
-                    "Profile job {} status: {} (queue position {}, {}s)",
-                    job_id, job_status, pos, elapsed
-                )),
-                None => cb(format!(
-                    "Profile job {} status: {} ({}s)",
-                    job_id, job_status, elapsed
-                )),
+        if last_status.as_deref() != Some(job_status) {
+            sink.emit(SubmissionEvent::StatusChanged {
+                status: job_status.to_string(),
+            });
+            last_status = Some(job_status.to_string());
+        }
+        if queue_position != last_position {
+            if let Some(position) = queue_position {
+                sink.emit(SubmissionEvent::Queued {
+                    runner: None,
+                    position,
+                    eta_seconds: None,
+                });
             }
+            last_position = queue_position;
         }
 
         match job_status {
             "succeeded" => {
-                let artifacts = download_profile_artifacts(client, base_url, &job).await?;
+                let artifacts =
+                    download_profile_artifacts(client, base_url, &job_id, &job, sink).await?;
+                for artifact in &artifacts {
+                    sink.emit(SubmissionEvent::ProfileArtifact {
+                        path: Some(artifact.zip_path.clone()),
+                        url: None,
+                    });
+                }
+                save_run_logs(
+                    sink,
+                    &job_id,
+                    &[RunLog {
+                        label: "1-profile".to_string(),
+                        stdout: non_empty_str(&job["stdout"]),
+                        stderr: non_empty_str(&job["stderr"]),
+                        result: &job,
+                    }],
+                );
                 let mut result = job;
                 result["downloaded_artifacts"] = Value::Array(
                     artifacts
@@ -838,117 +1263,46 @@ impl DownloadedProfileArtifact {
 async fn download_profile_artifacts(
     client: &Client,
     base_url: &str,
+    job_id: &str,
     job: &Value,
+    sink: &EventSink,
 ) -> Result<Vec<DownloadedProfileArtifact>> {
     let artifacts = job
         .get("artifacts")
         .and_then(|v| v.as_array())
         .ok_or_else(|| anyhow!("Profiler job did not include artifacts"))?;
 
-    let mut saved = Vec::new();
-    for artifact in artifacts {
-        let name = artifact
-            .get("name")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow!("Profiler artifact missing name"))?;
-        let url = artifact
-            .get("url")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| anyhow!("Profiler artifact missing url"))?;
-        let artifact_url = if url.starts_with("http://") || url.starts_with("https://") {
-            url.to_string()
-        } else {
-            format!("{}{}", base_url, url)
-        };
-        let bytes = client
-            .get(artifact_url)
-            .timeout(Duration::from_secs(120))
-            .send()
-            .await?
-            .error_for_status()?
-            .bytes()
-            .await?;
-        let zip_path = PathBuf::from(name);
-        std::fs::write(&zip_path, bytes.as_ref())
-            .map_err(|e| anyhow!("Failed to write profile artifact {}: {}", name, e))?;
-        let extracted = extract_profile_artifacts(&zip_path, bytes.as_ref())?;
-        saved.push(DownloadedProfileArtifact {
-            zip_path,
-            details: extracted.details,
-            reports: extracted.reports,
-        });
-    }
-    Ok(saved)
-}
-
-#[derive(Debug)]
-struct ExtractedProfileArtifacts {
-    details: Vec<PathBuf>,
-    reports: Vec<PathBuf>,
-}
-
-fn extract_profile_artifacts(zip_path: &Path, bytes: &[u8]) -> Result<ExtractedProfileArtifacts> {
-    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|e| {
-        anyhow!(
-            "Failed to read profile artifact {}: {}",
-            zip_path.display(),
-            e
-        )
-    })?;
-    let extract_dir = zip_path.with_extension("");
-    std::fs::create_dir_all(&extract_dir).map_err(|e| {
-        anyhow!(
-            "Failed to create profile report directory {}: {}",
-            extract_dir.display(),
-            e
-        )
-    })?;
-
-    let mut details = Vec::new();
-    let mut reports = Vec::new();
-    for idx in 0..archive.len() {
-        let mut entry = archive.by_index(idx).map_err(|e| {
-            anyhow!(
-                "Failed to read profile artifact entry in {}: {}",
-                zip_path.display(),
-                e
-            )
-        })?;
-        if !entry.name().ends_with(".ncu-rep")
-            && !entry.name().ends_with("ncu-details.txt")
-            && !entry.name().ends_with("ncu-details.csv")
-        {
-            continue;
-        }
-
-        let file_name = Path::new(entry.name())
-            .file_name()
-            .ok_or_else(|| anyhow!("Profile artifact contains an invalid report path"))?;
-        let mut output_path = extract_dir.join(file_name);
-        if output_path.exists() {
-            let stem = output_path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("profile");
-            let extension = output_path
-                .extension()
-                .and_then(|s| s.to_str())
-                .map(|ext| format!(".{}", ext))
-                .unwrap_or_default();
-            output_path = extract_dir.join(format!("{}-{}{}", stem, idx, extension));
-        }
-
-        let mut output = StdFile::create(&output_path)
-            .map_err(|e| anyhow!("Failed to create {}: {}", output_path.display(), e))?;
-        std::io::copy(&mut entry, &mut output)
-            .map_err(|e| anyhow!("Failed to extract {}: {}", output_path.display(), e))?;
-        if output_path.extension().and_then(|s| s.to_str()) == Some("ncu-rep") {
-            reports.push(output_path);
-        } else {
-            details.push(output_path);
-        }
-    }
-    Ok(ExtractedProfileArtifacts { details, reports })
+    // Artifact names come from the server: keep every write inside this
+    // job's artifacts directory.
+    let dir = artifacts::artifacts_dir(job_id)?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| anyhow!("Failed to create {}: {}", dir.display(), e))?;
+    let sources = artifacts
+        .iter()
+        .map(|artifact| ArtifactSource::from_json(artifact, base_url))
+        .collect::<Result<Vec<_>>>()?;
+    // Distinct server names can sanitize to the same file; each download
+    // needs its own.
+    let file_names = artifacts::unique_artifact_names(sources.iter().map(|s| s.name.as_str()))?;
+    let downloads: Vec<_> = sources
+        .into_iter()
+        .zip(file_names)
+        .map(|(source, file_name)| (source, dir.join(file_name)))
+        .collect();
+    download::download_all(client, &downloads, sink).await?;
+
+    downloads
+        .into_iter()
+        .map(|(_, zip_path)| {
+            let extracted =
+                artifacts::extract_profile_artifacts(&zip_path, ExtractLimits::default())?;
+            Ok(DownloadedProfileArtifact {
+                zip_path,
+                details: extracted.details,
+                reports: extracted.reports,
+            })
+        })
+        .collect()
 }
 
 fn file_url(path: &Path) -> String {
@@ -980,15 +1334,41 @@ async fn response_error_text(resp: reqwest::Response) -> Result<String> {
         .unwrap_or(text))
 }
 
-async fn submit_solution_background<P: AsRef<Path>>(
+/// Submit a non-profile run and poll until it finishes, returning the
+/// submission's final details rather than formatted text.
+pub async fn submit_solution_background<P: AsRef<Path>>(
     client: &Client,
     filepath: P,
     file_content: &[u8],
     leaderboard: &str,
     gpu: &str,
-    submission_mode: &str,
-    on_log: Option<Box<dyn Fn(String) + Send + Sync>>,
-) -> Result<String> {
+    submission_mode: SubmissionMode,
+    events: Option<EventSender>,
+) -> Result<SubmissionDetails> {
+    let sink = EventSink::new(events);
+    let result = run_submission_background(
+        client,
+        filepath,
+        file_content,
+        leaderboard,
+        gpu,
+        submission_mode,
+        &sink,
+    )
+    .await;
+    sink.finish(&result);
+    result
+}
+
+async fn run_submission_background<P: AsRef<Path>>(
+    client: &Client,
+    filepath: P,
+    file_content: &[u8],
+    leaderboard: &str,
+    gpu: &str,
+    submission_mode: SubmissionMode,
+    sink: &EventSink,
+) -> Result<SubmissionDetails> {
     let base_url =
         env::var("POPCORN_API_URL").map_err(|_| anyhow!("POPCORN_API_URL is not set"))?;
 
@@ -998,99 +1378,192 @@ async fn submit_solution_background<P: AsRef<Path>>(
         .ok_or_else(|| anyhow!("Invalid filepath"))?
         .to_string_lossy();
 
-    let part = Part::bytes(file_content.to_vec()).file_name(filename.to_string());
-    let form = Form::new().part("file", part);
     let url = format!(
         "{}/submission/{}/{}/{}",
         base_url,
         leaderboard.to_lowercase(),
         gpu,
-        submission_mode.to_lowercase()
+        submission_mode
     );
 
-    let resp = client
-        .post(&url)
-        .multipart(form)
-        .timeout(Duration::from_secs(60))
-        .send()
-        .await?;
+    // One key per attempt: retries of this upload reuse it so the server can
+    // dedupe them, while a later `popcorn submit` gets a fresh nonce. That
+    // includes an upload that timed out after the server took it: the retry
+    // finds the existing submission rather than starting another.
+    let idempotency_key = history::idempotency_key(
+        file_content,
+        leaderboard,
+        gpu,
+        submission_mode.as_str(),
+        fastrand::u64(..),
+    );
+    let log = |msg: String| sink.notice(msg);
+    let resp = retry::send_idempotent(
+        || {
+            let part = Part::bytes(file_content.to_vec()).file_name(filename.to_string());
+            client
+                .post(&url)
+                .header(IDEMPOTENCY_KEY_HEADER, &idempotency_key)
+                .multipart(Form::new().part("file", part))
+                .timeout(Duration::from_secs(60))
+        },
+        "Submission upload",
+        Some(&log),
+    )
+    .await?;
 
     let status = resp.status();
-    if !status.is_success() {
+    let accepted: Value = if status.is_success() {
+        resp.json().await?
+    } else {
         let error_text = resp.text().await?;
-        let detail = serde_json::from_str::<Value>(&error_text)
-            .ok()
-            .and_then(|v| v.get("detail").and_then(|d| d.as_str()).map(str::to_string));
-
-        return Err(anyhow!(
-            "Server returned status {}: {}",
-            status,
-            detail.unwrap_or(error_text)
-        ));
-    }
-
-    let accepted: Value = resp.json().await?;
+        let body = serde_json::from_str::<Value>(&error_text).ok();
+        // A replay of an upload the server already took: follow the existing
+        // submission instead of failing.
+        match body.as_ref().and_then(already_accepted_submission_id) {
+            Some(existing_id) if status == StatusCode::CONFLICT => {
+                sink.notice(format!(
+                    "Submission {} was already accepted for this upload. Following it...",
+                    existing_id
+                ));
+                serde_json::json!({ "details": { "id": existing_id } })
+            }
+            _ => {
+                let detail = body
+                    .as_ref()
+                    .and_then(|v| v.get("detail").and_then(|d| d.as_str()).map(str::to_string));
+                return Err(anyhow!(
+                    "Server returned status {}: {}",
+                    status,
+                    detail.unwrap_or(error_text)
+                ));
+            }
+        }
+    };
     let submission_id = accepted
         .get("details")
         .and_then(|v| v.get("id"))
         .and_then(|v| v.as_i64())
         .ok_or_else(|| anyhow!("Server did not return a submission id"))?;
     let accepted_runner_queue = parse_runner_queue(accepted.get("runner_queue"));
+    let _active = ActiveJobGuard::register(RemoteJob::Submission(submission_id));
 
-    if let Some(ref cb) = on_log {
-        match runner_queue_summary(accepted_runner_queue.as_ref()) {
-            Some(queue_summary) => cb(format!(
-                "Submission {} accepted. {}. Waiting for results...",
-                submission_id, queue_summary
-            )),
-            None => cb(format!(
-                "Submission {} accepted. Waiting for results...",
-                submission_id
-            )),
-        }
+    let record = SubmissionRecord::new(
+        submission_id,
+        leaderboard,
+        gpu,
+        submission_mode.as_str(),
+        file_content,
+        &filename,
+    );
+    if let Err(e) = history::record_submission(&record) {
+        sink.notice(format!(
+            "Warning: failed to record submission locally: {}",
+            e
+        ));
+    }
+
+    sink.emit(SubmissionEvent::Accepted {
+        id: submission_id.to_string(),
+    });
+    let mut last_queue = queue_event(accepted_runner_queue.as_ref(), None);
+    if let Some(event) = last_queue.clone() {
+        sink.emit(event);
     }
 
+    let mut queue_tracker = QueueTracker::default();
+    let mut last_status = None;
     let mut elapsed = 0;
+    let poll_url = format!("{}/user/submissions/{}", base_url, submission_id);
     loop {
-        let details = get_user_submission(client, submission_id).await?;
+        let polled = retry::send(
+            client.get(&poll_url).timeout(Duration::from_secs(30)),
+            Some(&log),
+        )
+        .await;
+        if let Some(failure) = transient_poll_failure(&polled) {
+            if elapsed >= SUBMISSION_POLL_TIMEOUT_SECONDS {
+                return Err(anyhow!(
+                    "Failed to poll submission {}: {}",
+                    submission_id,
+                    failure
+                ));
+            }
+            sink.notice(format!(
+                "Polling submission {} failed ({}). Trying again in {}s...",
+                submission_id, failure, SUBMISSION_POLL_INTERVAL_SECONDS
+            ));
+            sleep(Duration::from_secs(SUBMISSION_POLL_INTERVAL_SECONDS)).await;
+            elapsed += SUBMISSION_POLL_INTERVAL_SECONDS;
+            continue;
+        }
+        let details = submission_details_from_response(polled?).await?;
         let job_status = details
             .job
             .as_ref()
             .and_then(|job| job.status.as_deref())
             .unwrap_or(if details.done { "done" } else { "pending" });
 
-        if let Some(ref cb) = on_log {
-            match runner_queue_summary(details.runner_queue.as_ref()) {
-                Some(queue_summary) => cb(format!(
-                    "Submission {} status: {} - {} ({}s)",
-                    submission_id, job_status, queue_summary, elapsed
-                )),
-                None => cb(format!(
-                    "Submission {} status: {} ({}s)",
-                    submission_id, job_status, elapsed
-                )),
+        if let Some(queued_jobs) = details.runner_queue.as_ref().and_then(|q| q.queued_jobs) {
+            queue_tracker.record(elapsed, queued_jobs);
+        }
+
+        if last_status.as_deref() != Some(job_status) {
+            sink.emit(SubmissionEvent::StatusChanged {
+                status: job_status.to_string(),
+            });
+            last_status = Some(job_status.to_string());
+        }
+        if let Some(event) = queue_tracker.event(details.runner_queue.as_ref()) {
+            if last_queue.as_ref() != Some(&event) {
+                sink.emit(event.clone());
+                last_queue = Some(event);
             }
         }
 
+        if details.done || matches!(job_status, "failed" | "timed_out" | "hacked") {
+            save_run_logs(
+                sink,
+                &submission_id.to_string(),
+                &submission_run_logs(&details),
+            );
+        }
+
         match job_status {
             "failed" | "timed_out" | "hacked" => {
-                let error = details
-                    .job
-                    .as_ref()
-                    .and_then(|job| job.error.as_deref())
-                    .unwrap_or("No error details were provided");
-                return Err(anyhow!(
-                    "Submission {} {}: {}",
-                    submission_id,
-                    job_status,
-                    error
-                ));
+                let job_error = details.job.as_ref().and_then(|job| job.error.as_deref());
+                let error = job_error.unwrap_or("No error details were provided");
+                let mut message = format!("Submission {} {}: {}", submission_id, job_status, error);
+                if job_status == "hacked" {
+                    let findings = details
+                        .job
+                        .as_ref()
+                        .map_or(&[][..], |job| job.findings.as_slice());
+                    let path = filepath.as_ref().display().to_string();
+                    let source = std::str::from_utf8(file_content)
+                        .ok()
+                        .map(|code| (path.as_str(), code));
+                    let explanation = verdict::explain_findings(findings, source);
+                    let flagged =
+                        FlaggedVerdict::new(&record, job_error, findings, explanation.clone());
+                    if let Err(e) = flagged::record_flagged(&flagged) {
+                        sink.notice(format!("Warning: failed to record verdict locally: {}", e));
+                    }
+                    if !explanation.is_empty() {
+                        message.push('\n');
+                        message.push_str(&explanation);
+                    }
+                    message.push_str(
+                        "\nThe verdict is saved locally; review it with `popcorn submissions flagged`.",
+                    );
+                }
+                return Err(anyhow!(message));
             }
             _ => {}
         }
 
         if details.done {
-            return format_submission_details(&details);
+            return Ok(details);
         }
 
         if elapsed >= SUBMISSION_POLL_TIMEOUT_SECONDS {
@@ -1106,6 +1579,35 @@ async fn submit_solution_background<P: AsRef<Path>>(
     }
 }
 
+/// Why a poll of a long-running job failed in a way worth waiting out: a
+/// dropped connection or a retryable status that outlasted the per-request
+/// retries. A run can take up to an hour, so pollers keep going until their
+/// overall deadline instead of failing on one blip.
+fn transient_poll_failure(polled: &Result<reqwest::Response>) -> Option<String> {
+    match polled {
+        Ok(resp) if retry::is_retryable_status(resp.status()) => {
+            Some(format!("server returned {}", resp.status()))
+        }
+        Ok(_) => None,
+        // A request that could not even be built will not get better.
+        Err(e)
+            if e.downcast_ref::<reqwest::Error>()
+                .is_some_and(reqwest::Error::is_builder) =>
+        {
+            None
+        }
+        Err(e) => Some(e.to_string()),
+    }
+}
+
+/// The submission id in a 409 "already accepted" response, sent either as
+/// `submission_id` or, like a normal acceptance, as `details.id`.
+fn already_accepted_submission_id(body: &Value) -> Option<i64> {
+    body.get("submission_id")
+        .or_else(|| body.get("details").and_then(|d| d.get("id")))
+        .and_then(|v| v.as_i64())
+}
+
 /// Build a human-readable summary of the geomean leaderboard score(s) for a
 /// finished submission, or `None` if it has no scored `leaderboard` run.
 ///
@@ -1133,7 +1635,7 @@ fn leaderboard_score_summary(details: &SubmissionDetails) -> Option<String> {
     }
 }
 
-fn format_submission_details(details: &SubmissionDetails) -> Result<String> {
+pub fn format_submission_details(details: &SubmissionDetails) -> Result<String> {
     let runs: Vec<Value> = details
         .runs
         .iter()
@@ -1146,6 +1648,8 @@ fn format_submission_details(details: &SubmissionDetails) -> Result<String> {
                 "passed": run.passed,
                 "start_time": run.start_time,
                 "end_time": run.end_time,
+                "tests": run.tests,
+                "benchmarks": run.benchmarks,
             })
         })
         .collect();
@@ -1159,11 +1663,24 @@ fn format_submission_details(details: &SubmissionDetails) -> Result<String> {
     }))
     .map_err(|e| anyhow!("Failed to format submission result: {}", e))?;
 
-    // Lead with the geomean score so it is not lost in the runs JSON.
-    match leaderboard_score_summary(details) {
-        Some(summary) => Ok(format!("{}\n\n{}", summary, json)),
-        None => Ok(json),
+    // Lead with the geomean score and per-shape tables so they are not lost
+    // in the runs JSON.
+    let mut sections: Vec<String> = leaderboard_score_summary(details).into_iter().collect();
+    for run in &details.runs {
+        let results = RunResults {
+            tests: run.tests.clone(),
+            benchmarks: run.benchmarks.clone(),
+        };
+        if let Some(tables) = format_run_results(&results) {
+            let scope = if run.secret { " (secret)" } else { "" };
+            sections.push(format!(
+                "{}{} on {}:\n{}",
+                run.mode, scope, run.runner, tables
+            ));
+        }
     }
+    sections.push(json);
+    Ok(sections.join("\n\n"))
 }
 
 async fn submit_solution_streaming<P: AsRef<Path>>(
@@ -1172,8 +1689,8 @@ async fn submit_solution_streaming<P: AsRef<Path>>(
     file_content: &[u8],
     leaderboard: &str,
     gpu: &str,
-    submission_mode: &str,
-    on_log: Option<Box<dyn Fn(String) + Send + Sync>>,
+    submission_mode: SubmissionMode,
+    sink: &EventSink,
 ) -> Result<String> {
     let base_url =
         env::var("POPCORN_API_URL").map_err(|_| anyhow!("POPCORN_API_URL is not set"))?;
@@ -1184,257 +1701,334 @@ async fn submit_solution_streaming<P: AsRef<Path>>(
         .ok_or_else(|| anyhow!("Invalid filepath"))?
         .to_string_lossy();
 
-    let part = Part::bytes(file_content.to_vec()).file_name(filename.to_string());
-
-    let form = Form::new().part("file", part);
-
     let url = format!(
         "{}/{}/{}/{}",
         base_url,
         leaderboard.to_lowercase(),
         gpu,
-        submission_mode.to_lowercase()
+        submission_mode
     );
 
-    let resp = client
-        .post(&url)
-        .multipart(form)
-        .timeout(Duration::from_secs(3600))
-        .send()
-        .await?;
-
+    let idempotency_key = history::idempotency_key(
+        file_content,
+        leaderboard,
+        gpu,
+        submission_mode.as_str(),
+        fastrand::u64(..),
+    );
+    // Reconnecting re-posts the same upload: the idempotency key lets the
+    // server attach to the run it already started, and Last-Event-ID tells it
+    // where to resume the stream.
+    let connect = |last_event_id: Option<&str>| {
+        let part = Part::bytes(file_content.to_vec()).file_name(filename.to_string());
+        let mut request = client
+            .post(&url)
+            .header(IDEMPOTENCY_KEY_HEADER, &idempotency_key)
+            .multipart(Form::new().part("file", part))
+            .timeout(Duration::from_secs(3600));
+        if let Some(id) = last_event_id {
+            request = request.header(LAST_EVENT_ID_HEADER, id);
+        }
+        request.send()
+    };
+    let resp = connect(None).await?;
     let status = resp.status();
     if !status.is_success() {
-        let error_text = resp.text().await?;
-        let detail = serde_json::from_str::<Value>(&error_text)
-            .ok()
-            .and_then(|v| v.get("detail").and_then(|d| d.as_str()).map(str::to_string));
-
         return Err(anyhow!(
             "Server returned status {}: {}",
             status,
-            detail.unwrap_or(error_text)
+            response_error_text(resp).await?
         ));
     }
 
-    if resp
-        .headers()
+    if is_event_stream(&resp) {
+        let policy = retry::RetryPolicy::from_env();
+        let (result, event) =
+            follow_event_stream(resp, connect, &policy, submission_mode, sink).await?;
+        if let Ok(result_val) = serde_json::from_str(&event.data) {
+            save_run_logs(
+                sink,
+                &stream_submission_id(&result_val),
+                &stream_run_logs(&result_val),
+            );
+        }
+        Ok(result)
+    } else {
+        let result: Value = resp.json().await?;
+        let pretty_result = match result.get("results") {
+            Some(result_obj) => serde_json::to_string_pretty(result_obj)?,
+            None => return Err(anyhow!("Invalid non-streaming response structure")),
+        };
+        Ok(pretty_result)
+    }
+}
+
+/// Follow a submission's event stream to its final result, reconnecting
+/// through `connect` with `Last-Event-ID` whenever the connection drops.
+/// Returns the result and the `result` event it came from.
+async fn follow_event_stream<F, Fut>(
+    resp: reqwest::Response,
+    connect: F,
+    policy: &retry::RetryPolicy,
+    submission_mode: SubmissionMode,
+    sink: &EventSink,
+) -> Result<(String, SseEvent)>
+where
+    F: Fn(Option<&str>) -> Fut,
+    Fut: Future<Output = reqwest::Result<reqwest::Response>>,
+{
+    let mut decoder = SseDecoder::default();
+    let mut connection = Ok(resp);
+    let mut reconnects = 0;
+    loop {
+        let connected_at = Instant::now();
+        let mut delivered = false;
+        let interruption = match connection {
+            Ok(mut resp) => loop {
+                match resp.chunk().await {
+                    Ok(Some(chunk)) => {
+                        for event in decoder.feed(&chunk) {
+                            delivered = true;
+                            if let Some(result) =
+                                handle_stream_event(&event, submission_mode, sink)?
+                            {
+                                return Ok((result, event));
+                            }
+                        }
+                    }
+                    Ok(None) => break "stream closed".to_string(),
+                    Err(e) => break e.to_string(),
+                }
+            },
+            Err(reason) => reason,
+        };
+
+        // Events alone do not restore the budget: a stream that drops right
+        // after every event would otherwise reconnect forever.
+        if delivered && connected_at.elapsed() >= STREAM_HEALTHY_AFTER {
+            reconnects = 0;
+        }
+        if reconnects >= policy.max_retries {
+            return Err(anyhow!(
+                "Stream ended unexpectedly without a final result or error event ({}, after {} reconnect attempts).",
+                interruption,
+                reconnects
+            ));
+        }
+        let delay = decoder
+            .retry()
+            .unwrap_or_else(|| policy.backoff(reconnects));
+        reconnects += 1;
+        sink.notice(format!(
+            "Event stream interrupted ({}). Reconnecting in {:.1}s (attempt {}/{})...",
+            interruption,
+            delay.as_secs_f64(),
+            reconnects,
+            policy.max_retries
+        ));
+        sleep(delay).await;
+
+        decoder.reset_connection();
+        connection = match connect(decoder.last_event_id()).await {
+            Ok(resp) if retry::is_retryable_status(resp.status()) => {
+                Err(format!("server returned {}", resp.status()))
+            }
+            Ok(resp) if !resp.status().is_success() => {
+                let status = resp.status();
+                return Err(anyhow!(
+                    "Server returned status {} while reconnecting: {}",
+                    status,
+                    response_error_text(resp).await?
+                ));
+            }
+            Ok(resp) if !is_event_stream(&resp) => {
+                return Err(anyhow!(
+                    "Server did not resume the event stream after reconnecting"
+                ));
+            }
+            Ok(resp) => Ok(resp),
+            Err(e) => Err(e.to_string()),
+        };
+    }
+}
+
+fn is_event_stream(resp: &reqwest::Response) -> bool {
+    resp.headers()
         .get(reqwest::header::CONTENT_TYPE)
         .and_then(|v| v.to_str().ok())
         .is_some_and(|s| s.starts_with("text/event-stream"))
-    {
-        let mut resp = resp;
-        let mut buffer = String::new();
-        let mut stderr = tokio::io::stderr();
-
-        while let Some(chunk) = resp.chunk().await? {
-            buffer.push_str(&String::from_utf8_lossy(&chunk));
-
-            while let Some(pos) = buffer.find("\n\n") {
-                let message_str = buffer.drain(..pos + 2).collect::<String>();
-                let mut event_type = None;
-                let mut data_json = None;
-
-                for line in message_str.lines() {
-                    if let Some(stripped) = line.strip_prefix("event:") {
-                        event_type = Some(stripped.trim());
-                    } else if let Some(stripped) = line.strip_prefix("data:") {
-                        data_json = Some(stripped.trim());
-                    }
-                }
+}
 
-                if let (Some(event), Some(data)) = (event_type, data_json) {
-                    match event {
-                        "status" => {
-                            if let Some(ref cb) = on_log {
-                                // Try to parse as JSON and extract "message" or just return raw data
-                                if let Ok(val) = serde_json::from_str::<Value>(data) {
-                                    if let Some(msg) = val.get("message").and_then(|m| m.as_str()) {
-                                        cb(msg.to_string());
-                                    } else {
-                                        cb(data.to_string());
-                                    }
-                                } else {
-                                    cb(data.to_string());
+/// Act on one event from a streamed submission: `Some` carries the final
+/// result, an `error` event becomes `Err`, anything else is progress.
+fn handle_stream_event(
+    event: &SseEvent,
+    submission_mode: SubmissionMode,
+    sink: &EventSink,
+) -> Result<Option<String>> {
+    let data = event.data.as_str();
+    match event.event.as_str() {
+        "status" => {
+            // Try to parse as JSON and extract "message" or just use raw data
+            let status = serde_json::from_str::<Value>(data)
+                .ok()
+                .and_then(|val| {
+                    val.get("message")
+                        .and_then(|m| m.as_str())
+                        .map(str::to_string)
+                })
+                .unwrap_or_else(|| data.to_string());
+            sink.emit(SubmissionEvent::StatusChanged { status });
+        }
+        "result" => {
+            let result_val: Value = serde_json::from_str(data)?;
+
+            // Handle "results" array
+            if let Some(results_array) = result_val.get("results").and_then(|v| v.as_array()) {
+                // Special handling for profile mode
+                if submission_mode == SubmissionMode::Profile {
+                    for (i, result_item) in results_array.iter().enumerate() {
+                        if let Some(runs) = result_item.get("runs").and_then(|r| r.as_object()) {
+                            for (key, run_data) in runs.iter() {
+                                if key.starts_with("profile") {
+                                    handle_profile_result(sink, run_data, i, key);
                                 }
                             }
                         }
-                        "result" => {
-                            let result_val: Value = serde_json::from_str(data)?;
-
-                            if let Some(ref cb) = on_log {
-                                // Handle "results" array
-                                if let Some(results_array) =
-                                    result_val.get("results").and_then(|v| v.as_array())
-                                {
-                                    let mode_key = submission_mode.to_lowercase();
-
-                                    // Special handling for profile mode
-                                    if mode_key == "profile" {
-                                        for (i, result_item) in results_array.iter().enumerate() {
-                                            if let Some(runs) =
-                                                result_item.get("runs").and_then(|r| r.as_object())
-                                            {
-                                                for (key, run_data) in runs.iter() {
-                                                    if key.starts_with("profile") {
-                                                        handle_profile_result(cb, run_data, i, key);
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    } else {
-                                        // Existing handling for non-profile modes
-                                        for (i, result_item) in results_array.iter().enumerate() {
-                                            if let Some(run_obj) = result_item
-                                                .get("runs")
-                                                .and_then(|r| r.get(&mode_key))
-                                                .and_then(|t| t.get("run"))
-                                            {
-                                                if let Some(stdout) =
-                                                    run_obj.get("stdout").and_then(|s| s.as_str())
-                                                {
-                                                    if !stdout.is_empty() {
-                                                        cb(format!(
-                                                            "STDOUT (Run {}):\n{}",
-                                                            i + 1,
-                                                            stdout
-                                                        ));
-                                                    }
-                                                }
-                                                // Also check stderr
-                                                if let Some(stderr) =
-                                                    run_obj.get("stderr").and_then(|s| s.as_str())
-                                                {
-                                                    if !stderr.is_empty() {
-                                                        cb(format!(
-                                                            "STDERR (Run {}):\n{}",
-                                                            i + 1,
-                                                            stderr
-                                                        ));
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                } else {
-                                    // Fallback for single object or different structure
-                                    if let Some(stdout) =
-                                        result_val.get("stdout").and_then(|s| s.as_str())
-                                    {
-                                        if !stdout.is_empty() {
-                                            cb(format!("STDOUT:\n{}", stdout));
-                                        }
-                                    }
+                    }
+                } else {
+                    // Existing handling for non-profile modes
+                    for (i, result_item) in results_array.iter().enumerate() {
+                        if let Some(run_obj) = result_item
+                            .get("runs")
+                            .and_then(|r| r.get(submission_mode.as_str()))
+                            .and_then(|t| t.get("run"))
+                        {
+                            // Prefer the per-shape results; fall back to raw
+                            // stdout when the run reported none.
+                            let results = parse_run_results(&run_obj["result"]);
+                            if !results.tests.is_empty() || !results.benchmarks.is_empty() {
+                                sink.emit(SubmissionEvent::BenchmarkResult {
+                                    run: i + 1,
+                                    results,
+                                });
+                            } else if let Some(stdout) =
+                                run_obj.get("stdout").and_then(|s| s.as_str())
+                            {
+                                if !stdout.is_empty() {
+                                    sink.emit(SubmissionEvent::Stdout {
+                                        run: Some(i + 1),
+                                        text: stdout.to_string(),
+                                    });
                                 }
                             }
-
-                            if let Some(reports) = result_val.get("reports") {
-                                return Ok(reports.to_string());
-                            } else {
-                                // If no reports, return the whole result as a string
-                                return Ok(serde_json::to_string_pretty(&result_val)?);
-                            }
-                        }
-                        "error" => {
-                            let error_val: Value = serde_json::from_str(data)?;
-                            let detail = error_val
-                                .get("detail")
-                                .and_then(|d| d.as_str())
-                                .unwrap_or("Unknown server error");
-                            let status_code = error_val.get("status_code").and_then(|s| s.as_i64());
-                            let raw_error = error_val.get("raw_error").and_then(|e| e.as_str());
-
-                            let mut error_msg = format!("Server processing error: {}", detail);
-                            if let Some(sc) = status_code {
-                                error_msg.push_str(&format!(" (Status Code: {})", sc));
-                            }
-                            if let Some(re) = raw_error {
-                                error_msg.push_str(&format!(" | Raw Error: {}", re));
-                            }
-
-                            return Err(anyhow!(error_msg));
-                        }
-                        _ => {
-                            let msg = format!("Ignoring unknown SSE event: {}\n", event);
-                            if let Some(ref cb) = on_log {
-                                cb(msg.clone());
+                            // Also check stderr
+                            if let Some(stderr) = run_obj.get("stderr").and_then(|s| s.as_str()) {
+                                if !stderr.is_empty() {
+                                    sink.emit(SubmissionEvent::Stderr {
+                                        run: Some(i + 1),
+                                        text: stderr.to_string(),
+                                    });
+                                }
                             }
-                            stderr.write_all(msg.as_bytes()).await?;
-                            stderr.flush().await?;
                         }
                     }
                 }
+            } else {
+                // Fallback for single object or different structure
+                if let Some(stdout) = result_val.get("stdout").and_then(|s| s.as_str()) {
+                    if !stdout.is_empty() {
+                        sink.emit(SubmissionEvent::Stdout {
+                            run: None,
+                            text: stdout.to_string(),
+                        });
+                    }
+                }
+            }
+
+            if let Some(reports) = result_val.get("reports") {
+                return Ok(Some(reports.to_string()));
+            } else {
+                // If no reports, return the whole result as a string
+                return Ok(Some(serde_json::to_string_pretty(&result_val)?));
             }
         }
-        Err(anyhow!(
-            "Stream ended unexpectedly without a final result or error event."
-        ))
-    } else {
-        let result: Value = resp.json().await?;
-        let pretty_result = match result.get("results") {
-            Some(result_obj) => serde_json::to_string_pretty(result_obj)?,
-            None => return Err(anyhow!("Invalid non-streaming response structure")),
-        };
-        Ok(pretty_result)
+        "error" => {
+            let error_val: Value = serde_json::from_str(data)?;
+            let detail = error_val
+                .get("detail")
+                .and_then(|d| d.as_str())
+                .unwrap_or("Unknown server error");
+            let status_code = error_val.get("status_code").and_then(|s| s.as_i64());
+            let raw_error = error_val.get("raw_error").and_then(|e| e.as_str());
+
+            let mut error_msg = format!("Server processing error: {}", detail);
+            if let Some(sc) = status_code {
+                error_msg.push_str(&format!(" (Status Code: {})", sc));
+            }
+            if let Some(re) = raw_error {
+                error_msg.push_str(&format!(" | Raw Error: {}", re));
+            }
+
+            return Err(anyhow!(error_msg));
+        }
+        // Events without an `event:` field carry nothing the CLI uses.
+        "message" => {}
+        other => sink.notice(format!("Ignoring unknown SSE event: {}", other)),
     }
+    Ok(None)
 }
 
-/// Handle profile mode results by decoding and displaying profile data,
-/// and saving trace files to the current directory.
-fn handle_profile_result(
-    cb: &(dyn Fn(String) + Send + Sync),
-    run_data: &Value,
-    result_idx: usize,
-    run_key: &str,
-) {
+/// Handle profile mode results by decoding profile reports and saving trace
+/// files to the current directory.
+fn handle_profile_result(sink: &EventSink, run_data: &Value, result_idx: usize, run_key: &str) {
     // 1. Get profiler type and display it
     if let Some(profile) = run_data.get("profile") {
         let profiler = profile
             .get("profiler")
             .and_then(|p| p.as_str())
             .unwrap_or("Unknown");
-        cb(format!("\n=== Profiler: {} ===", profiler));
+        sink.notice(format!("=== Profiler: {} ===", profiler));
 
-        // 2. Decode and display profile report from run.result
+        // 2. Decode the profile reports from run.result
         if let Some(run) = run_data.get("run") {
-            // Display stdout/stderr if present
             if let Some(stdout) = run.get("stdout").and_then(|s| s.as_str()) {
                 if !stdout.is_empty() {
-                    cb(format!("STDOUT:\n{}", stdout));
+                    sink.emit(SubmissionEvent::Stdout {
+                        run: None,
+                        text: stdout.to_string(),
+                    });
                 }
             }
             if let Some(stderr) = run.get("stderr").and_then(|s| s.as_str()) {
                 if !stderr.is_empty() {
-                    cb(format!("STDERR:\n{}", stderr));
+                    sink.emit(SubmissionEvent::Stderr {
+                        run: None,
+                        text: stderr.to_string(),
+                    });
                 }
             }
 
             // Extract and decode profile report from result
             if let Some(result) = run.get("result").and_then(|r| r.as_object()) {
-                let bench_count = result
-                    .get("benchmark-count")
-                    .and_then(|c| c.as_i64())
-                    .unwrap_or(0);
-
-                for i in 0..bench_count {
-                    // Get benchmark spec
-                    let spec_key = format!("benchmark.{}.spec", i);
-                    let spec = result
-                        .get(&spec_key)
-                        .and_then(|s| s.as_str())
-                        .unwrap_or("unknown");
-                    cb(format!("\nBenchmark: {}", spec));
-
-                    // Decode and display the profile report
+                let benchmarks = parse_run_results(&run["result"]).benchmarks;
+
+                for (i, benchmark) in benchmarks.iter().enumerate() {
                     let report_key = format!("benchmark.{}.report", i);
                     if let Some(encoded_report) = result.get(&report_key).and_then(|r| r.as_str()) {
                         match base64::engine::general_purpose::STANDARD.decode(encoded_report) {
                             Ok(decoded) => {
-                                if let Ok(report_text) = String::from_utf8(decoded) {
-                                    cb(format!("\n{}", report_text));
+                                if let Ok(report) = String::from_utf8(decoded) {
+                                    sink.emit(SubmissionEvent::ProfileReport {
+                                        profiler: profiler.to_string(),
+                                        spec: benchmark.spec.clone(),
+                                        report,
+                                    });
                                 }
                             }
-                            Err(e) => cb(format!("Failed to decode profile report: {}", e)),
+                            Err(e) => {
+                                sink.notice(format!("Failed to decode profile report: {}", e))
+                            }
                         }
                     }
                 }
@@ -1448,24 +2042,103 @@ fn handle_profile_result(
                     Ok(trace_data) => {
                         match write_profile_trace_file(&trace_data, Utc::now(), result_idx, run_key)
                         {
-                            Ok(filename) => cb(format!("\nSaved profile trace to: {}", filename)),
-                            Err(e) => cb(format!("Failed to save trace file: {}", e)),
+                            Ok(filename) => sink.emit(SubmissionEvent::ProfileArtifact {
+                                path: Some(PathBuf::from(filename)),
+                                url: None,
+                            }),
+                            Err(e) => sink.notice(format!("Failed to save trace file: {}", e)),
                         }
                     }
-                    Err(e) => cb(format!("Failed to decode trace data: {}", e)),
+                    Err(e) => sink.notice(format!("Failed to decode trace data: {}", e)),
                 }
             }
         }
 
-        // 4. Show download URL if available
+        // 4. Offer the download URL if available
         if let Some(url) = profile.get("download_url").and_then(|u| u.as_str()) {
             if !url.is_empty() {
-                cb(format!("Download full profile: {}", url));
+                sink.emit(SubmissionEvent::ProfileArtifact {
+                    path: None,
+                    url: Some(url.to_string()),
+                });
             }
         }
     }
 }
 
+/// Save each run's output under the run log root and report where. A failed
+/// save only warns: the submission itself went through.
+fn save_run_logs(sink: &EventSink, submission_id: &str, logs: &[RunLog]) {
+    if logs.is_empty() {
+        return;
+    }
+    let root = runs::runs_root();
+    match runs::save_run_logs(&root, submission_id, logs) {
+        Ok(files) => sink.emit(SubmissionEvent::LogsSaved {
+            dir: root.join(submission_id),
+            files,
+        }),
+        Err(e) => sink.notice(format!("Warning: failed to save run logs: {}", e)),
+    }
+}
+
+/// Log files for each run of a polled submission, labelled by position, mode
+/// and, for secret runs, `-secret`.
+fn submission_run_logs(details: &SubmissionDetails) -> Vec<RunLog<'_>> {
+    details
+        .runs
+        .iter()
+        .enumerate()
+        .map(|(i, run)| RunLog {
+            label: format!(
+                "{}-{}{}",
+                i + 1,
+                sanitize_profile_run_key(&run.mode),
+                if run.secret { "-secret" } else { "" }
+            ),
+            stdout: run.stdout.as_deref(),
+            stderr: run.stderr.as_deref(),
+            result: &run.raw_result,
+        })
+        .collect()
+}
+
+/// Log files for each run in a streamed `result` event, labelled by result
+/// position and run key.
+fn stream_run_logs(result: &Value) -> Vec<RunLog<'_>> {
+    let mut logs = Vec::new();
+    let items = result.get("results").and_then(Value::as_array);
+    for (i, item) in items.into_iter().flatten().enumerate() {
+        let Some(item_runs) = item.get("runs").and_then(Value::as_object) else {
+            continue;
+        };
+        for (key, run_data) in item_runs {
+            let run = &run_data["run"];
+            logs.push(RunLog {
+                label: format!("{}-{}", i + 1, sanitize_profile_run_key(key)),
+                stdout: non_empty_str(&run["stdout"]),
+                stderr: non_empty_str(&run["stderr"]),
+                result: &run["result"],
+            });
+        }
+    }
+    logs
+}
+
+fn non_empty_str(value: &Value) -> Option<&str> {
+    value.as_str().filter(|text| !text.is_empty())
+}
+
+/// The id to file a streamed submission's logs under: the server's, when the
+/// result carries one, otherwise a local timestamp.
+fn stream_submission_id(result: &Value) -> String {
+    match &result["submission_id"] {
+        Value::Number(id) => id.to_string(),
+        Value::String(id) if !id.is_empty() => id.clone(),
+        _ => format!("local-{}", Utc::now().format("%Y%m%d-%H%M%S")),
+    }
+}
+
 fn sanitize_profile_run_key(run_key: &str) -> String {
     let sanitized: String = run_key
         .chars()
@@ -1513,6 +2186,7 @@ fn write_profile_trace_file(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::fixtures::{details, run};
     use chrono::TimeZone;
     use tempfile::tempdir;
 
@@ -1595,7 +2269,7 @@ mod tests {
             b"print('hello')",
             "test-leaderboard",
             "H100",
-            "test",
+            SubmissionMode::Test,
             None,
         )
         .await;
@@ -1685,36 +2359,140 @@ mod tests {
         assert_eq!(parse_score(&Value::Null), None);
     }
 
-    fn run(mode: &str, secret: bool, score: Option<f64>) -> SubmissionRun {
-        SubmissionRun {
-            start_time: None,
-            end_time: None,
-            mode: mode.to_string(),
-            secret,
-            runner: "B200".to_string(),
-            score,
-            passed: true,
-        }
+    #[test]
+    fn test_parse_run_results_reads_indexed_keys() {
+        use serde_json::json;
+
+        let result = json!({
+            "test-count": "2",
+            "test.0.spec": "m: 64",
+            "test.0.status": "pass",
+            "test.1.spec": "m: 128",
+            "test.1.status": "fail",
+            "test.1.error": "mismatch at index 3",
+            "benchmark-count": "2",
+            "benchmark.0.spec": "m: 128; n: 64",
+            "benchmark.0.mean": "1520.5",
+            "benchmark.0.std": "12.5",
+            "benchmark.0.best": "1500",
+            "benchmark.0.worst": "1600",
+            "benchmark.0.runs": "100",
+            "benchmark.1.spec": "m: 256; n: 64",
+            "benchmark.1.mean": 3100.0,
+        });
+
+        let results = parse_run_results(&result);
+
+        assert_eq!(
+            results.tests,
+            vec![
+                TestResult {
+                    spec: "m: 64".to_string(),
+                    status: "pass".to_string(),
+                    passed: true,
+                    ..Default::default()
+                },
+                TestResult {
+                    spec: "m: 128".to_string(),
+                    status: "fail".to_string(),
+                    passed: false,
+                    error: Some("mismatch at index 3".to_string()),
+                    ..Default::default()
+                },
+            ]
+        );
+        assert_eq!(
+            results.benchmarks,
+            vec![
+                BenchmarkResult {
+                    spec: "m: 128; n: 64".to_string(),
+                    mean: Some(1520.5),
+                    std: Some(12.5),
+                    min: Some(1500.0),
+                    max: Some(1600.0),
+                    runs: Some(100),
+                },
+                BenchmarkResult {
+                    spec: "m: 256; n: 64".to_string(),
+                    mean: Some(3100.0),
+                    ..Default::default()
+                },
+            ]
+        );
+        assert_eq!(parse_run_results(&Value::Null), RunResults::default());
     }
 
-    fn details(runs: Vec<SubmissionRun>) -> SubmissionDetails {
-        SubmissionDetails {
-            id: 1,
-            leaderboard_id: 1,
-            leaderboard_name: "qr_v2".to_string(),
-            file_name: "submission.py".to_string(),
-            user_id: "u".to_string(),
-            submission_time: String::new(),
-            done: true,
-            code: String::new(),
-            runs,
-            job: None,
-            runner_queue: None,
-        }
+    #[test]
+    fn test_format_benchmark_table_aligns_columns() {
+        let table = format_benchmark_table(&[BenchmarkResult {
+            spec: "m: 128".to_string(),
+            mean: Some(1520.5),
+            std: Some(12.5),
+            min: Some(1500.0),
+            max: Some(1600.0),
+            runs: Some(100),
+        }]);
+
+        assert_eq!(
+            table,
+            "Benchmark         Mean          Std          Min          Max    Runs\n\
+             m: 128         1.52 µs        12 ns      1.50 µs      1.60 µs     100"
+        );
+    }
+
+    #[test]
+    fn test_parse_run_results_reads_mismatch_stats() {
+        let result = serde_json::json!({
+            "test-count": 3,
+            "test.0.spec": "m: 64",
+            "test.0.status": "fail",
+            "test.0.error": "mismatch found! Max abs error: 3.1e-2, max rel error: 0.12.",
+            "test.1.spec": "m: 128",
+            "test.1.status": "fail",
+            "test.1.max_abs_error": "0.5",
+            "test.1.error": "output mismatch\n  at index 3: 1.0 vs 1.5",
+            "test.2.spec": "m: 256",
+            "test.2.status": "pass",
+        });
+
+        let tests = parse_run_results(&result).tests;
+
+        assert_eq!(tests[0].max_abs_error, Some(0.031));
+        assert_eq!(tests[0].max_rel_error, Some(0.12));
+        assert_eq!(tests[1].max_abs_error, Some(0.5));
+        assert_eq!(tests[1].max_rel_error, None);
+        assert_eq!(
+            format_failure_table(&tests).unwrap(),
+            "Failures (2 of 3 tests):\n\
+             \x20 Test    Status  Max abs err  Max rel err  Error\n\
+             ▶ m: 64   fail       3.100e-2     1.200e-1  mismatch found! Max abs error: 3.1e-2, max rel error: 0.12.\n\
+             \x20 m: 128  fail       5.000e-1            -  output mismatch\n\
+             \n\
+             Error for m: 128:\n\
+             \x20 output mismatch\n\
+             \x20   at index 3: 1.0 vs 1.5"
+        );
+        assert_eq!(format_failure_table(&tests[2..]), None);
+    }
+
+    #[test]
+    fn test_format_submission_details_includes_run_tables() {
+        let mut benchmark_run = run("benchmark", false, None);
+        benchmark_run.benchmarks = vec![BenchmarkResult {
+            spec: "m: 128".to_string(),
+            mean: Some(1520.5),
+            ..Default::default()
+        }];
+        let d = details(1, vec![benchmark_run]);
+
+        let formatted = format_submission_details(&d).unwrap();
+
+        assert!(formatted.starts_with("benchmark on B200:\nBenchmark"));
+        assert!(formatted.contains("\"benchmarks\""));
     }
 
     #[test]
-    fn test_runner_queue_summary_reports_queued_jobs() {
+    fn test_queue_event_reports_queued_jobs() {
         let queue = RunnerQueueStatus {
             runner: Some("Modal".to_string()),
             gpu: Some("B200".to_string()),
@@ -1722,21 +2500,53 @@ mod tests {
         };
 
         assert_eq!(
-            runner_queue_summary(Some(&queue)),
+            queue_event(Some(&queue), None).map(|event| event.to_string()),
             Some("Modal B200 queue: 5 queued jobs".to_string())
         );
     }
 
+    #[test]
+    fn test_parse_supported_modes_skips_unknown_modes() {
+        let supported = parse_supported_modes(&serde_json::json!({
+            "modes": ["Test", "benchmark", "script", "test"],
+            "profilers": ["ncu"]
+        }));
+        assert_eq!(
+            supported.modes,
+            vec![SubmissionMode::Test, SubmissionMode::Benchmark]
+        );
+        assert_eq!(supported.profilers, vec!["ncu".to_string()]);
+    }
+
+    #[test]
+    fn test_already_accepted_submission_id() {
+        assert_eq!(
+            already_accepted_submission_id(&serde_json::json!({ "submission_id": 42 })),
+            Some(42)
+        );
+        assert_eq!(
+            already_accepted_submission_id(&serde_json::json!({ "details": { "id": 7 } })),
+            Some(7)
+        );
+        assert_eq!(
+            already_accepted_submission_id(&serde_json::json!({ "detail": "conflict" })),
+            None
+        );
+    }
+
     #[test]
     fn test_leaderboard_score_summary_reports_geomean_scores() {
         // Only the scored `leaderboard` runs are reported; test/benchmark and
         // null-score runs are skipped.
-        let d = details(vec![
-            run("test", false, None),
-            run("benchmark", false, None),
-            run("leaderboard", false, Some(0.0066)),
-            run("leaderboard", true, Some(0.0018)),
-        ]);
+        let d = details(
+            1,
+            vec![
+                run("test", false, None),
+                run("benchmark", false, None),
+                run("leaderboard", false, Some(0.0066)),
+                run("leaderboard", true, Some(0.0018)),
+            ],
+        );
         let summary = leaderboard_score_summary(&d).expect("expected a score summary");
         assert_eq!(
             summary,
@@ -1753,13 +2563,184 @@ mod tests {
     fn test_leaderboard_score_summary_none_without_scored_leaderboard_run() {
         // A submission with no scored leaderboard run (e.g. test/benchmark
         // mode, or scores not yet populated) yields no summary.
-        let d = details(vec![
-            run("test", false, None),
-            run("benchmark", false, Some(0.5)),
-            run("leaderboard", false, None),
-        ]);
+        let d = details(
+            1,
+            vec![
+                run("test", false, None),
+                run("benchmark", false, Some(0.5)),
+                run("leaderboard", false, None),
+            ],
+        );
         assert!(leaderboard_score_summary(&d).is_none());
         // format_submission_details still works, just without a summary header.
         assert!(format_submission_details(&d).unwrap().starts_with('{'));
     }
+
+    #[test]
+    fn test_stream_result_emits_run_events() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let sink = EventSink::new(Some(tx));
+        let event = SseEvent {
+            event: "result".to_string(),
+            data: serde_json::json!({
+                "results": [{"runs": {"test": {"run": {
+                    "stdout": "ignored when results are structured",
+                    "stderr": "warning: slow path",
+                    "result": {"test-count": "1", "test.0.spec": "k: 128", "test.0.status": "pass"}
+                }}}}]
+            })
+            .to_string(),
+            id: None,
+        };
+
+        let result = handle_stream_event(&event, SubmissionMode::Test, &sink).unwrap();
+
+        assert!(result.is_some());
+        match rx.try_recv().unwrap() {
+            SubmissionEvent::BenchmarkResult { run, results } => {
+                assert_eq!(run, 1);
+                assert_eq!(results.tests[0].spec, "k: 128");
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+        assert_eq!(
+            rx.try_recv().unwrap(),
+            SubmissionEvent::Stderr {
+                run: Some(1),
+                text: "warning: slow path".to_string()
+            }
+        );
+        assert!(rx.try_recv().is_err());
+    }
+
+    /// Answer one connection per body with an event stream that ends after
+    /// the body, and report the `Last-Event-ID` each request carried.
+    async fn serve_event_streams(
+        bodies: Vec<&'static str>,
+    ) -> (String, tokio::task::JoinHandle<Vec<Option<String>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/stream", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let mut last_event_ids = Vec::new();
+            for body in bodies {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 4096];
+                let n = socket.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+                last_event_ids.push(
+                    request
+                        .lines()
+                        .find_map(|line| line.strip_prefix("last-event-id: "))
+                        .map(str::to_string),
+                );
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\nconnection: close\r\n\r\n{}",
+                    body
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+            last_event_ids
+        });
+        (url, server)
+    }
+
+    fn fast_policy(max_retries: u32) -> retry::RetryPolicy {
+        retry::RetryPolicy {
+            max_retries,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_follow_event_stream_resumes_truncated_stream() {
+        let (url, server) = serve_event_streams(vec![
+            "id: 1\nevent: status\ndata: {\"message\": \"compiling\"}\n\n",
+            "id: 2\nevent: result\ndata: {\"stdout\": \"ok\"}\n\n",
+        ])
+        .await;
+        let client = Client::new();
+        let connect = |last_event_id: Option<&str>| {
+            let mut request = client.get(&url);
+            if let Some(id) = last_event_id {
+                request = request.header(LAST_EVENT_ID_HEADER, id);
+            }
+            request.send()
+        };
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let sink = EventSink::new(Some(sender));
+
+        let resp = connect(None).await.unwrap();
+        let (result, event) =
+            follow_event_stream(resp, connect, &fast_policy(2), SubmissionMode::Test, &sink)
+                .await
+                .unwrap();
+
+        assert!(result.contains("\"ok\""), "{}", result);
+        assert_eq!(event.id.as_deref(), Some("2"));
+        assert_eq!(server.await.unwrap(), vec![None, Some("1".to_string())]);
+        let mut statuses = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            if let SubmissionEvent::StatusChanged { status } = event {
+                statuses.push(status);
+            }
+        }
+        assert_eq!(statuses, vec!["compiling"]);
+    }
+
+    #[tokio::test]
+    async fn test_follow_event_stream_gives_up_on_a_stream_that_keeps_dropping() {
+        // Every connection delivers an event before dropping, which must not
+        // reset the reconnect budget.
+        let body = "id: 1\nevent: status\ndata: running\n\n";
+        let (url, server) = serve_event_streams(vec![body; 10]).await;
+        let client = Client::new();
+        let connect = |_: Option<&str>| client.get(&url).send();
+        let sink = EventSink::new(None);
+
+        let resp = connect(None).await.unwrap();
+        let err = follow_event_stream(resp, connect, &fast_policy(2), SubmissionMode::Test, &sink)
+            .await
+            .unwrap_err();
+
+        assert!(
+            err.to_string().contains("after 2 reconnect attempts"),
+            "{}",
+            err
+        );
+        // Three connections in, the server is still waiting with bodies left.
+        assert!(!server.is_finished());
+        server.abort();
+    }
+
+    #[test]
+    fn test_stream_run_logs_label_each_run() {
+        let result = serde_json::json!({
+            "submission_id": 42,
+            "results": [{"runs": {
+                "test": {"run": {"stdout": "ok", "stderr": "", "result": {"success": true}}},
+                "profile.0": {"run": {"stdout": "trace", "result": {}}}
+            }}]
+        });
+        let logs = stream_run_logs(&result);
+        let labels: Vec<_> = logs.iter().map(|log| log.label.as_str()).collect();
+        assert_eq!(labels, vec!["1-profile_0", "1-test"]);
+        assert_eq!(logs[1].stdout, Some("ok"));
+        assert_eq!(logs[1].stderr, None);
+        assert_eq!(logs[1].result["success"], true);
+        assert_eq!(stream_submission_id(&result), "42");
+        assert!(stream_submission_id(&Value::Null).starts_with("local-"));
+    }
+
+    #[test]
+    fn test_run_output_falls_back_to_meta() {
+        let run =
+            serde_json::json!({ "stdout": "", "meta": { "stdout": "from meta", "stderr": "err" } });
+        assert_eq!(run_output(&run, "stdout").as_deref(), Some("from meta"));
+        assert_eq!(run_output(&run, "stderr").as_deref(), Some("err"));
+        let run = serde_json::json!({ "meta": { "stdout": "from meta" } });
+        assert_eq!(run_output(&run, "stdout").as_deref(), Some("from meta"));
+    }
 }
@@ -0,0 +1,203 @@
+use anyhow::{anyhow, Result};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::env;
+use std::time::Duration;
+use tokio::time::sleep;
+
+use super::events::{EventSender, EventSink, SubmissionEvent};
+use super::retry;
+use super::{
+    parse_runner_queue, queue_event, transient_poll_failure, SUBMISSION_POLL_INTERVAL_SECONDS,
+};
+use crate::models::RunnerQueueStatus;
+
+/// Queue samples older than this are ignored when estimating the drain rate,
+/// so the ETA follows the current load rather than the whole wait.
+const QUEUE_RATE_WINDOW_SECONDS: u64 = 300;
+
+/// Tracks a runner's queue length across polls to estimate when a queued job
+/// will start.
+#[derive(Debug, Default)]
+pub struct QueueTracker {
+    samples: VecDeque<(u64, i64)>,
+}
+
+impl QueueTracker {
+    /// Record the queue length seen `elapsed` seconds into the wait.
+    pub fn record(&mut self, elapsed: u64, queued_jobs: i64) {
+        self.samples.push_back((elapsed, queued_jobs));
+        while let Some(&(first, _)) = self.samples.front() {
+            if elapsed.saturating_sub(first) <= QUEUE_RATE_WINDOW_SECONDS {
+                break;
+            }
+            self.samples.pop_front();
+        }
+    }
+
+    /// Estimated seconds until the queue empties, from how fast it has been
+    /// draining. `None` until it has been seen to shrink.
+    pub fn eta_seconds(&self) -> Option<u64> {
+        let &(first_at, first_len) = self.samples.front()?;
+        let &(last_at, last_len) = self.samples.back()?;
+        if last_len <= 0 {
+            return Some(0);
+        }
+        let drained = first_len - last_len;
+        if drained <= 0 || last_at <= first_at {
+            return None;
+        }
+        let jobs_per_second = drained as f64 / (last_at - first_at) as f64;
+        Some((last_len as f64 / jobs_per_second).ceil() as u64)
+    }
+
+    /// The `Queued` event for `queue`, with the current ETA.
+    pub fn event(&self, queue: Option<&RunnerQueueStatus>) -> Option<SubmissionEvent> {
+        queue_event(queue, self.eta_seconds())
+    }
+}
+
+pub fn format_eta(seconds: u64) -> String {
+    match seconds {
+        0 => "any moment".to_string(),
+        s if s < 60 => format!("~{}s", s),
+        s if s < 3600 => format!("~{}m {}s", s / 60, s % 60),
+        s => format!("~{}h {}m", s / 3600, (s % 3600) / 60),
+    }
+}
+
+/// Current queue for a leaderboard's GPU, or `None` if the server does not
+/// report one.
+pub async fn fetch_runner_queue(
+    client: &Client,
+    leaderboard: &str,
+    gpu: &str,
+) -> Result<Option<RunnerQueueStatus>> {
+    let resp = retry::send(queue_request(client, leaderboard, gpu)?, None).await?;
+    runner_queue_from_response(resp).await
+}
+
+fn queue_request(client: &Client, leaderboard: &str, gpu: &str) -> Result<RequestBuilder> {
+    let base_url =
+        env::var("POPCORN_API_URL").map_err(|_| anyhow!("POPCORN_API_URL is not set"))?;
+    Ok(client
+        .get(format!(
+            "{}/queue/{}/{}",
+            base_url,
+            leaderboard.to_lowercase(),
+            gpu
+        ))
+        .timeout(Duration::from_secs(30)))
+}
+
+async fn runner_queue_from_response(resp: Response) -> Result<Option<RunnerQueueStatus>> {
+    let status = resp.status();
+    if status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        let error_text = resp.text().await?;
+        return Err(anyhow!("Server returned status {}: {}", status, error_text));
+    }
+
+    let body: Value = resp.json().await?;
+    Ok(parse_runner_queue(Some(&body)))
+}
+
+/// Hold a submission locally until fewer than `max_queue` jobs are queued on
+/// the runner. A server that does not report queues lets it through; failed
+/// polls are waited out, since a hold can last all night.
+pub async fn wait_for_queue_below(
+    client: &Client,
+    leaderboard: &str,
+    gpu: &str,
+    max_queue: i64,
+    events: Option<&EventSender>,
+) -> Result<()> {
+    let sink = EventSink::new(events.cloned());
+    let mut tracker = QueueTracker::default();
+    let mut last_event = None;
+    let mut elapsed = 0;
+    let mut holding = false;
+    loop {
+        let polled = retry::send(queue_request(client, leaderboard, gpu)?, None).await;
+        if let Some(failure) = transient_poll_failure(&polled) {
+            sink.notice(format!(
+                "Checking the {} queue failed ({}). Trying again in {}s...",
+                gpu, failure, SUBMISSION_POLL_INTERVAL_SECONDS
+            ));
+            sleep(Duration::from_secs(SUBMISSION_POLL_INTERVAL_SECONDS)).await;
+            elapsed += SUBMISSION_POLL_INTERVAL_SECONDS;
+            continue;
+        }
+        let queue = runner_queue_from_response(polled?).await?;
+        let Some(queued_jobs) = queue.as_ref().and_then(|q| q.queued_jobs) else {
+            sink.notice(format!(
+                "No queue information for {} on {}; submitting now.",
+                leaderboard, gpu
+            ));
+            return Ok(());
+        };
+        if queued_jobs < max_queue {
+            return Ok(());
+        }
+
+        if !holding {
+            sink.notice(format!(
+                "Holding submission until fewer than {} jobs are queued.",
+                max_queue
+            ));
+            holding = true;
+        }
+        tracker.record(elapsed, queued_jobs);
+        let event = tracker.event(queue.as_ref());
+        if event != last_event {
+            if let Some(event) = event.clone() {
+                sink.emit(event);
+            }
+            last_event = event;
+        }
+
+        sleep(Duration::from_secs(SUBMISSION_POLL_INTERVAL_SECONDS)).await;
+        elapsed += SUBMISSION_POLL_INTERVAL_SECONDS;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_tracker_estimates_from_drain_rate() {
+        let mut tracker = QueueTracker::default();
+        tracker.record(0, 10);
+        assert_eq!(tracker.eta_seconds(), None);
+
+        tracker.record(10, 8);
+        tracker.record(20, 6);
+        // 4 jobs drained in 20s leaves 6 jobs at 0.2 jobs/s.
+        assert_eq!(tracker.eta_seconds(), Some(30));
+
+        tracker.record(25, 0);
+        assert_eq!(tracker.eta_seconds(), Some(0));
+    }
+
+    #[test]
+    fn test_queue_tracker_forgets_old_samples() {
+        let mut tracker = QueueTracker::default();
+        tracker.record(0, 100);
+        tracker.record(400, 10);
+        tracker.record(410, 10);
+        // The fast early drain is outside the window; the queue is now flat.
+        assert_eq!(tracker.eta_seconds(), None);
+    }
+
+    #[test]
+    fn test_format_eta() {
+        assert_eq!(format_eta(0), "any moment");
+        assert_eq!(format_eta(45), "~45s");
+        assert_eq!(format_eta(80), "~1m 20s");
+        assert_eq!(format_eta(7260), "~2h 1m");
+    }
+}
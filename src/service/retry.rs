@@ -0,0 +1,298 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+use std::env;
+use std::error::Error as StdError;
+use std::future::Future;
+use std::io;
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// Default number of retries after the first attempt; override with
+/// `POPCORN_MAX_RETRIES`.
+const DEFAULT_MAX_RETRIES: u32 = 4;
+const BASE_DELAY: Duration = Duration::from_millis(500);
+const MAX_DELAY: Duration = Duration::from_secs(30);
+/// Longest `Retry-After` the CLI will sit through before giving up.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(120);
+
+pub type LogCallback<'a> = dyn Fn(String) + Send + Sync + 'a;
+
+/// Retry policy shared by every service call: exponential backoff with
+/// jitter for idempotent requests that are rate limited, hit a gateway error,
+/// time out or lose their connection.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_delay: BASE_DELAY,
+            max_delay: MAX_DELAY,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn from_env() -> Self {
+        let max_retries = env::var("POPCORN_MAX_RETRIES")
+            .ok()
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(DEFAULT_MAX_RETRIES);
+        Self {
+            max_retries,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before retry number `attempt` (0-based): half the exponential
+    /// step plus a random share of the other half, so concurrent clients
+    /// spread out without any retry becoming immediate.
+    pub(super) fn backoff(&self, attempt: u32) -> Duration {
+        let step = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        let half = step / 2;
+        half + half.mul_f64(fastrand::f64())
+    }
+}
+
+/// Send `request`, retrying idempotent methods on 429/502/503/504, timeouts
+/// and connection resets; a `Retry-After` header sets the delay. Each retry
+/// is reported through `on_log` when one is given.
+pub async fn send(request: RequestBuilder, on_log: Option<&LogCallback<'_>>) -> Result<Response> {
+    let (client, request) = request.build_split();
+    let request = request?;
+    let idempotent = matches!(*request.method(), Method::GET | Method::HEAD);
+    if !idempotent || request.try_clone().is_none() {
+        return Ok(client.execute(request).await?);
+    }
+
+    let description = format!("{} {}", request.method(), request.url().path());
+    send_with_policy(&RetryPolicy::from_env(), &description, on_log, || {
+        // Bodiless requests always clone.
+        client.execute(request.try_clone().expect("request without a body"))
+    })
+    .await
+}
+
+/// Send a request that carries an `Idempotency-Key`, so the server dedupes
+/// replays and even a POST is safe to retry, including one that timed out
+/// after the server may already have acted on it. `build` is called once per
+/// attempt because multipart bodies cannot be cloned.
+pub async fn send_idempotent<F>(
+    build: F,
+    description: &str,
+    on_log: Option<&LogCallback<'_>>,
+) -> Result<Response>
+where
+    F: Fn() -> RequestBuilder,
+{
+    send_with_policy(&RetryPolicy::from_env(), description, on_log, || {
+        build().send()
+    })
+    .await
+}
+
+async fn send_with_policy<F, Fut>(
+    policy: &RetryPolicy,
+    description: &str,
+    on_log: Option<&LogCallback<'_>>,
+    mut attempt_request: F,
+) -> Result<Response>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = reqwest::Result<Response>>,
+{
+    let mut attempt = 0;
+    loop {
+        let result = attempt_request().await;
+        if attempt >= policy.max_retries {
+            return Ok(result?);
+        }
+
+        let (reason, delay) = match result {
+            Ok(resp) if is_retryable_status(resp.status()) => {
+                let delay = retry_after(&resp).unwrap_or_else(|| policy.backoff(attempt));
+                (format!("server returned {}", resp.status()), delay)
+            }
+            Ok(resp) => return Ok(resp),
+            Err(err) if err.is_timeout() || is_connection_reset(&err) => {
+                (err.to_string(), policy.backoff(attempt))
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        attempt += 1;
+        if let Some(cb) = on_log {
+            cb(format!(
+                "{} failed ({}). Retrying in {:.1}s (attempt {}/{})...",
+                description,
+                reason,
+                delay.as_secs_f64(),
+                attempt,
+                policy.max_retries
+            ));
+        }
+        sleep(delay).await;
+    }
+}
+
+pub(super) fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+fn is_connection_reset(err: &reqwest::Error) -> bool {
+    if err.is_connect() {
+        return true;
+    }
+    let mut source = err.source();
+    while let Some(cause) = source {
+        if let Some(io_err) = cause.downcast_ref::<io::Error>() {
+            return matches!(
+                io_err.kind(),
+                io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::BrokenPipe
+                    | io::ErrorKind::UnexpectedEof
+            );
+        }
+        source = cause.source();
+    }
+    false
+}
+
+fn retry_after(resp: &Response) -> Option<Duration> {
+    let value = resp.headers().get(RETRY_AFTER)?.to_str().ok()?;
+    parse_retry_after(value, Utc::now())
+}
+
+/// Parse a `Retry-After` header given either as delay-seconds or an HTTP date.
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    let delay = match value.parse::<u64>() {
+        Ok(seconds) => Duration::from_secs(seconds),
+        Err(_) => {
+            let at = DateTime::parse_from_rfc2822(value).ok()?;
+            (at.with_timezone(&Utc) - now)
+                .to_std()
+                .unwrap_or(Duration::ZERO)
+        }
+    };
+    Some(delay.min(MAX_RETRY_AFTER))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+        };
+        for attempt in 0..10 {
+            let step = Duration::from_millis(100 * 2u64.pow(attempt)).min(policy.max_delay);
+            let delay = policy.backoff(attempt);
+            assert!(delay >= step / 2 && delay <= step, "{:?}", delay);
+        }
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(parse_retry_after("7", now), Some(Duration::from_secs(7)));
+        assert_eq!(
+            parse_retry_after("Thu, 01 Jan 2026 00:00:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 31 Dec 2025 23:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("3600", now), Some(MAX_RETRY_AFTER));
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_keyed_post_is_retried_with_same_key() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/submission", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let mut keys = Vec::new();
+            let mut stalled = Vec::new();
+            for attempt in 0..2 {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 4096];
+                let n = socket.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..n]).to_lowercase();
+                keys.extend(
+                    request
+                        .lines()
+                        .filter_map(|line| line.strip_prefix("idempotency-key: "))
+                        .map(str::to_string),
+                );
+                if attempt == 0 {
+                    // Accept the upload but never answer, like a server that
+                    // took the submission before the client gave up.
+                    stalled.push(socket);
+                } else {
+                    socket
+                        .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\n\r\n{}")
+                        .await
+                        .unwrap();
+                }
+            }
+            keys
+        });
+
+        let client = reqwest::Client::new();
+        let policy = RetryPolicy {
+            max_retries: 1,
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(10),
+        };
+        let resp = send_with_policy(&policy, "Submission upload", None, || {
+            client
+                .post(&url)
+                .header("Idempotency-Key", "key-1")
+                .body("code")
+                .timeout(Duration::from_millis(300))
+                .send()
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(server.await.unwrap(), vec!["key-1", "key-1"]);
+    }
+
+    #[test]
+    fn test_rate_limits_and_gateway_errors_are_retried() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(is_retryable_status(StatusCode::GATEWAY_TIMEOUT));
+        assert!(!is_retryable_status(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+    }
+}
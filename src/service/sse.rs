@@ -0,0 +1,261 @@
+use std::time::Duration;
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// One dispatched Server-Sent Event.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SseEvent {
+    /// The `event:` field, or `message` when the server sent none.
+    pub event: String,
+    /// Every `data:` line of the event, joined with `\n`.
+    pub data: String,
+    /// The last event id in effect when the event was dispatched.
+    pub id: Option<String>,
+}
+
+/// Incremental `text/event-stream` decoder following the WHATWG parsing
+/// rules: LF, CR and CRLF line endings, a leading BOM, comments, multi-line
+/// `data`, `id` and `retry`. Lines are only decoded once complete, so a
+/// multi-byte character split across network chunks survives intact.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    pending: Vec<u8>,
+    started: bool,
+    /// The previous chunk ended on CR; a leading LF belongs to that line break.
+    skip_lf: bool,
+    event_type: String,
+    data: String,
+    id_buffer: Option<String>,
+    last_event_id: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl SseDecoder {
+    /// Feed raw bytes from the response body and return the events they complete.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        let mut chunk = chunk;
+        if self.skip_lf && !chunk.is_empty() {
+            if chunk[0] == b'\n' {
+                chunk = &chunk[1..];
+            }
+            self.skip_lf = false;
+        }
+        self.pending.extend_from_slice(chunk);
+
+        if !self.started {
+            if self.pending.len() < BOM.len() && BOM.starts_with(&self.pending) {
+                return Vec::new();
+            }
+            if self.pending.starts_with(BOM) {
+                self.pending.drain(..BOM.len());
+            }
+            self.started = true;
+        }
+
+        let mut events = Vec::new();
+        let mut start = 0;
+        while let Some(offset) = self.pending[start..]
+            .iter()
+            .position(|&b| b == b'\n' || b == b'\r')
+        {
+            let end = start + offset;
+            let line = String::from_utf8_lossy(&self.pending[start..end]).into_owned();
+            start = end + 1;
+            if self.pending[end] == b'\r' {
+                match self.pending.get(start) {
+                    Some(b'\n') => start += 1,
+                    Some(_) => {}
+                    None => self.skip_lf = true,
+                }
+            }
+            if let Some(event) = self.process_line(&line) {
+                events.push(event);
+            }
+        }
+        self.pending.drain(..start);
+        events
+    }
+
+    /// Id to send as `Last-Event-ID` when reconnecting.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref().filter(|id| !id.is_empty())
+    }
+
+    /// Reconnection delay requested by the server with `retry:`.
+    pub fn retry(&self) -> Option<Duration> {
+        self.retry
+    }
+
+    /// Forget any half-received event before reading a new connection; the
+    /// last event id and retry delay carry over.
+    pub fn reset_connection(&mut self) {
+        self.pending.clear();
+        self.started = false;
+        self.skip_lf = false;
+        self.event_type.clear();
+        self.data.clear();
+        self.id_buffer = self.last_event_id.clone();
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event_type = value.to_string(),
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+            }
+            "id" if !value.contains('\0') => self.id_buffer = Some(value.to_string()),
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                if let Ok(ms) = value.parse() {
+                    self.retry = Some(Duration::from_millis(ms));
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        self.last_event_id = self.id_buffer.clone();
+        let event_type = std::mem::take(&mut self.event_type);
+        if self.data.is_empty() {
+            return None;
+        }
+        let mut data = std::mem::take(&mut self.data);
+        data.pop();
+        Some(SseEvent {
+            event: if event_type.is_empty() {
+                "message".to_string()
+            } else {
+                event_type
+            },
+            data,
+            id: self.last_event_id().map(str::to_string),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A profile run as the API streams it: comments as keep-alives, ids,
+    /// a retry hint and a multi-line result payload.
+    const PROFILE_STREAM: &[u8] = include_bytes!("testdata/profile_stream.txt");
+    /// The same kind of stream after a proxy rewrote it with a BOM and CRLF
+    /// line endings.
+    const CRLF_STREAM: &[u8] = include_bytes!("testdata/crlf_bom_stream.txt");
+
+    fn decode_in_chunks(stream: &[u8], chunk_size: usize) -> (Vec<SseEvent>, SseDecoder) {
+        let mut decoder = SseDecoder::default();
+        let events = stream
+            .chunks(chunk_size)
+            .flat_map(|chunk| decoder.feed(chunk))
+            .collect();
+        (events, decoder)
+    }
+
+    #[test]
+    fn test_decodes_recorded_profile_stream() {
+        let (events, decoder) = decode_in_chunks(PROFILE_STREAM, PROFILE_STREAM.len());
+        let kinds: Vec<&str> = events.iter().map(|e| e.event.as_str()).collect();
+        assert_eq!(kinds, vec!["status", "status", "message", "result"]);
+
+        assert_eq!(events[0].data, r#"{"message": "Submission accepted"}"#);
+        assert_eq!(events[0].id.as_deref(), Some("1"));
+        assert_eq!(events[2].data, "warming up ⏳");
+        // Multi-line data is joined with newlines.
+        let result: serde_json::Value = serde_json::from_str(&events[3].data).unwrap();
+        assert_eq!(result["reports"]["kernel"], "done ✓");
+        assert_eq!(decoder.last_event_id(), Some("4"));
+        assert_eq!(decoder.retry(), Some(Duration::from_millis(5000)));
+    }
+
+    #[test]
+    fn test_chunk_boundaries_do_not_change_events() {
+        let (whole, _) = decode_in_chunks(PROFILE_STREAM, PROFILE_STREAM.len());
+        // Size 1 splits every multi-byte character and CRLF pair.
+        for size in [1, 2, 3, 7, 64] {
+            assert_eq!(
+                decode_in_chunks(PROFILE_STREAM, size).0,
+                whole,
+                "chunk {}",
+                size
+            );
+        }
+        let (crlf, _) = decode_in_chunks(CRLF_STREAM, CRLF_STREAM.len());
+        for size in [1, 2, 5] {
+            assert_eq!(
+                decode_in_chunks(CRLF_STREAM, size).0,
+                crlf,
+                "chunk {}",
+                size
+            );
+        }
+    }
+
+    #[test]
+    fn test_crlf_and_bom_stream() {
+        let (events, decoder) = decode_in_chunks(CRLF_STREAM, 1);
+        assert_eq!(events.len(), 2);
+        // The BOM is stripped, so the first field is still recognised.
+        assert_eq!(events[0].event, "status");
+        assert_eq!(events[0].data, "queued\nposition 3");
+        assert_eq!(events[1].event, "error");
+        assert_eq!(events[1].id.as_deref(), Some("evt-7"));
+        assert_eq!(decoder.last_event_id(), Some("evt-7"));
+    }
+
+    #[test]
+    fn test_field_edge_cases() {
+        let mut decoder = SseDecoder::default();
+        let events = decoder.feed(
+            b"data\n\
+              \n\
+              data:no-space\n\
+              data:  two spaces\n\
+              retry: soon\n\
+              id: a\0b\n\
+              \n\
+              event: status\n\
+              \n\
+              id\n\
+              data: reset\n\
+              \n\
+              data: incomplete",
+        );
+        assert_eq!(events.len(), 3);
+        // A bare `data` field contributes an empty line.
+        assert_eq!(events[0].data, "");
+        assert_eq!(events[1].data, "no-space\n two spaces");
+        assert_eq!(decoder.retry(), None);
+        assert_eq!(events[1].id, None);
+        // An event with no data is not dispatched, and its type does not leak.
+        assert_eq!(events[2].event, "message");
+        // An empty `id` clears the last event id.
+        assert_eq!(events[2].id, None);
+        assert_eq!(decoder.last_event_id(), None);
+    }
+
+    #[test]
+    fn test_reset_connection_drops_partial_event() {
+        let mut decoder = SseDecoder::default();
+        let events = decoder.feed(b"id: 9\ndata: one\n\nevent: result\ndata: {\"half");
+        assert_eq!(events.len(), 1);
+        decoder.reset_connection();
+        let events = decoder.feed(b"event: result\ndata: {}\n\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].data, "{}");
+        assert_eq!(events[0].id.as_deref(), Some("9"));
+    }
+}
@@ -0,0 +1,184 @@
+use serde_json::Value;
+
+use crate::models::HackFinding;
+
+/// Keys of a job the server may put detection details under.
+const FINDING_KEYS: [&str; 4] = ["detection", "findings", "checks", "details"];
+/// Local lines linked per finding when matching by pattern.
+const MAX_LINKED_LINES: usize = 3;
+
+/// The anti-cheat findings attached to a job. The server may send a list of
+/// checks, a single check object, an object wrapping the list, or plain check
+/// names; anything it leaves out stays `None`.
+pub(super) fn parse_hack_findings(job: &Value) -> Vec<HackFinding> {
+    FINDING_KEYS
+        .iter()
+        .filter_map(|key| job.get(key))
+        .find(|value| !value.is_null())
+        .map(findings_from)
+        .unwrap_or_default()
+}
+
+fn findings_from(value: &Value) -> Vec<HackFinding> {
+    match value {
+        Value::Array(items) => items.iter().flat_map(findings_from).collect(),
+        Value::String(check) => vec![HackFinding {
+            check: check.clone(),
+            ..Default::default()
+        }],
+        Value::Object(object) => {
+            if let Some(nested) = ["findings", "checks"]
+                .iter()
+                .find_map(|key| object.get(*key).filter(|v| v.is_array()))
+            {
+                return findings_from(nested);
+            }
+            let text = |keys: &[&str]| {
+                keys.iter()
+                    .find_map(|key| object.get(*key).and_then(Value::as_str))
+                    .filter(|s| !s.is_empty())
+                    .map(str::to_string)
+            };
+            let line = ["line", "line_number", "lineno"]
+                .iter()
+                .find_map(|key| match object.get(*key)? {
+                    Value::Number(n) => n.as_u64(),
+                    Value::String(s) => s.parse().ok(),
+                    _ => None,
+                })
+                .and_then(|line| usize::try_from(line).ok());
+            vec![HackFinding {
+                check: text(&["check", "name", "rule"]).unwrap_or_else(|| "unknown".to_string()),
+                message: text(&["message", "reason", "detail"]),
+                pattern: text(&["pattern", "match"]),
+                line,
+                snippet: text(&["snippet", "code"]),
+            }]
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Explain a `hacked` verdict: each finding with the local lines it points
+/// at, as `path:line: code`. `source` is the submitted file and the path to
+/// show for it, when it is text. Empty if the server gave no details.
+pub fn explain_findings(findings: &[HackFinding], source: Option<(&str, &str)>) -> String {
+    if findings.is_empty() {
+        return String::new();
+    }
+    let mut lines = vec!["Flagged by:".to_string()];
+    for finding in findings {
+        match &finding.message {
+            Some(message) => lines.push(format!("  - {}: {}", finding.check, message)),
+            None => lines.push(format!("  - {}", finding.check)),
+        }
+        if let Some(pattern) = &finding.pattern {
+            lines.push(format!("      pattern: {}", pattern));
+        }
+        match source {
+            Some((path, code)) => {
+                let linked = linked_lines(finding, code);
+                if linked.is_empty() {
+                    if let Some(line) = finding.line {
+                        lines.push(format!("      {}:{}", path, line));
+                    } else if finding.pattern.is_some() || finding.snippet.is_some() {
+                        lines.push(format!("      (not found in {})", path));
+                    }
+                }
+                for (number, text) in linked {
+                    lines.push(format!("      {}:{}: {}", path, number, text.trim()));
+                }
+            }
+            None => {
+                if let Some(line) = finding.line {
+                    lines.push(format!("      line {}", line));
+                }
+            }
+        }
+        if let Some(snippet) = &finding.snippet {
+            if source.is_none() {
+                lines.push(format!("      {}", snippet.trim()));
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+/// Local lines (1-based) a finding points at: the reported line if it exists
+/// in `code`, otherwise lines containing the pattern or snippet.
+fn linked_lines<'a>(finding: &HackFinding, code: &'a str) -> Vec<(usize, &'a str)> {
+    let numbered = || code.lines().enumerate().map(|(i, line)| (i + 1, line));
+    if let Some(line) = finding.line {
+        if let Some(found) = numbered().find(|(number, _)| *number == line) {
+            return vec![found];
+        }
+    }
+    let needle = finding.pattern.as_deref().or_else(|| {
+        finding
+            .snippet
+            .as_deref()
+            .and_then(|s| s.lines().map(str::trim).find(|l| !l.is_empty()))
+    });
+    match needle.map(str::trim).filter(|n| !n.is_empty()) {
+        Some(needle) => numbered()
+            .filter(|(_, line)| line.contains(needle))
+            .take(MAX_LINKED_LINES)
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_hack_findings_accepts_server_shapes() {
+        let job = json!({
+            "status": "hacked",
+            "detection": { "checks": [
+                { "check": "stream_tampering", "reason": "replaces the timing stream", "line": "3" },
+                "output_caching",
+            ]}
+        });
+        let findings = parse_hack_findings(&job);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].check, "stream_tampering");
+        assert_eq!(
+            findings[0].message.as_deref(),
+            Some("replaces the timing stream")
+        );
+        assert_eq!(findings[0].line, Some(3));
+        assert_eq!(findings[1].check, "output_caching");
+        assert!(parse_hack_findings(&json!({ "status": "hacked" })).is_empty());
+    }
+
+    #[test]
+    fn test_explain_findings_links_local_lines() {
+        let code = "import torch\n_cache = {}\ntorch.cuda.synchronize = lambda: None\n";
+        let findings = vec![
+            HackFinding {
+                check: "timer_patch".to_string(),
+                pattern: Some("torch.cuda.synchronize =".to_string()),
+                ..Default::default()
+            },
+            HackFinding {
+                check: "output_caching".to_string(),
+                message: Some("results reused across calls".to_string()),
+                line: Some(2),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(
+            explain_findings(&findings, Some(("sub.py", code))),
+            "Flagged by:\n\
+             \x20 - timer_patch\n\
+             \x20     pattern: torch.cuda.synchronize =\n\
+             \x20     sub.py:3: torch.cuda.synchronize = lambda: None\n\
+             \x20 - output_caching: results reused across calls\n\
+             \x20     sub.py:2: _cache = {}"
+        );
+        assert_eq!(explain_findings(&[], Some(("sub.py", code))), "");
+    }
+}
@@ -1,12 +1,42 @@
-use anyhow::Result;
-use std::fs;
+use anyhow::{anyhow, Result};
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use std::fs::{self, File};
+use std::io::Read;
 use std::path::Path;
 
+/// Name of the optional manifest archive submissions can carry at their root.
+const ARCHIVE_MANIFEST_NAME: &str = "popcorn.yaml";
+/// Top-level source file inside an archive that is scanned for `#!POPCORN` lines.
+const ARCHIVE_SUBMISSION_NAME: &str = "submission.py";
+/// Upper bound on the size of a text entry read from an archive for directives.
+const MAX_ARCHIVE_TEXT_ENTRY_BYTES: u64 = 1024 * 1024;
+
 pub struct PopcornDirectives {
     pub leaderboard_name: String,
     pub gpus: Vec<String>,
 }
 
+impl PopcornDirectives {
+    fn empty() -> Self {
+        Self {
+            leaderboard_name: String::new(),
+            gpus: Vec::new(),
+        }
+    }
+}
+
+/// `popcorn.yaml` manifest shipped at the root of an archive submission.
+#[derive(Deserialize, Default)]
+struct PopcornManifest {
+    #[serde(default)]
+    leaderboard: Option<String>,
+    #[serde(default)]
+    gpu: Option<String>,
+    #[serde(default)]
+    gpus: Option<Vec<String>>,
+}
+
 pub fn is_archive_file<P: AsRef<Path>>(filepath: P) -> bool {
     let path = filepath.as_ref();
     let name = path
@@ -18,19 +48,142 @@ pub fn is_archive_file<P: AsRef<Path>>(filepath: P) -> bool {
 }
 
 pub fn get_popcorn_directives<P: AsRef<Path>>(filepath: P) -> Result<(PopcornDirectives, bool)> {
-    // Archive files (tarballs, zips) are binary and cannot contain directives
     if is_archive_file(&filepath) {
-        return Ok((
-            PopcornDirectives {
-                leaderboard_name: String::new(),
-                gpus: Vec::new(),
-            },
-            false,
-        ));
+        return get_archive_directives(filepath);
     }
 
     let content = fs::read_to_string(filepath)?;
+    Ok(parse_directives(&content))
+}
+
+/// Read directives from an archive submission. A root-level `popcorn.yaml`
+/// manifest takes precedence; any field it leaves unset falls back to the
+/// `#!POPCORN` lines of a root-level `submission.py`.
+fn get_archive_directives<P: AsRef<Path>>(filepath: P) -> Result<(PopcornDirectives, bool)> {
+    let entries = read_archive_text_entries(
+        filepath.as_ref(),
+        &[ARCHIVE_MANIFEST_NAME, ARCHIVE_SUBMISSION_NAME],
+    )?;
+
+    let (mut directives, mut has_multiple_gpus) = entries
+        .iter()
+        .find(|(name, _)| name == ARCHIVE_SUBMISSION_NAME)
+        .map(|(_, content)| parse_directives(content))
+        .unwrap_or_else(|| (PopcornDirectives::empty(), false));
+
+    if let Some((_, content)) = entries
+        .iter()
+        .find(|(name, _)| name == ARCHIVE_MANIFEST_NAME)
+    {
+        let manifest: PopcornManifest = serde_yaml::from_str::<Option<PopcornManifest>>(content)
+            .map_err(|e| {
+                anyhow!(
+                    "Failed to parse {} in archive: {}",
+                    ARCHIVE_MANIFEST_NAME,
+                    e
+                )
+            })?
+            .unwrap_or_default();
+
+        if let Some(leaderboard) = manifest.leaderboard.filter(|lb| !lb.trim().is_empty()) {
+            directives.leaderboard_name = leaderboard.trim().to_string();
+        }
+
+        let mut gpus: Vec<String> = manifest
+            .gpus
+            .unwrap_or_default()
+            .into_iter()
+            .chain(manifest.gpu)
+            .map(|gpu| gpu.trim().to_string())
+            .filter(|gpu| !gpu.is_empty())
+            .collect();
+        if !gpus.is_empty() {
+            has_multiple_gpus = gpus.len() > 1;
+            gpus.truncate(1);
+            directives.gpus = gpus;
+        }
+    }
 
+    Ok((directives, has_multiple_gpus))
+}
+
+/// Collect the contents of the requested root-level text entries of a `.zip`
+/// or `.tar.gz`/`.tgz` archive. Entries that are missing, too large or not
+/// valid UTF-8 are skipped.
+fn read_archive_text_entries(path: &Path, wanted: &[&str]) -> Result<Vec<(String, String)>> {
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_lowercase();
+    let file = File::open(path)?;
+    let mut found = Vec::new();
+
+    if name.ends_with(".zip") {
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| anyhow!("Failed to read archive {}: {}", path.display(), e))?;
+        for idx in 0..archive.len() {
+            let entry = archive
+                .by_index(idx)
+                .map_err(|e| anyhow!("Failed to read archive {}: {}", path.display(), e))?;
+            let Some(entry_name) = root_entry_name(entry.name(), wanted) else {
+                continue;
+            };
+            if !entry.is_file() || entry.size() > MAX_ARCHIVE_TEXT_ENTRY_BYTES {
+                continue;
+            }
+            if let Some(content) = read_text_entry(entry) {
+                found.push((entry_name, content));
+            }
+        }
+    } else {
+        let mut archive = tar::Archive::new(GzDecoder::new(file));
+        let entries = archive
+            .entries()
+            .map_err(|e| anyhow!("Failed to read archive {}: {}", path.display(), e))?;
+        for entry in entries {
+            let entry =
+                entry.map_err(|e| anyhow!("Failed to read archive {}: {}", path.display(), e))?;
+            let entry_path = entry
+                .path()
+                .map_err(|e| anyhow!("Failed to read archive {}: {}", path.display(), e))?
+                .to_string_lossy()
+                .to_string();
+            let Some(entry_name) = root_entry_name(&entry_path, wanted) else {
+                continue;
+            };
+            if !entry.header().entry_type().is_file()
+                || entry.header().size()? > MAX_ARCHIVE_TEXT_ENTRY_BYTES
+            {
+                continue;
+            }
+            if let Some(content) = read_text_entry(entry) {
+                found.push((entry_name, content));
+            }
+        }
+    }
+
+    Ok(found)
+}
+
+fn root_entry_name(entry_name: &str, wanted: &[&str]) -> Option<String> {
+    let normalized = entry_name.trim_start_matches("./");
+    wanted
+        .iter()
+        .find(|name| **name == normalized)
+        .map(|name| name.to_string())
+}
+
+fn read_text_entry<R: Read>(entry: R) -> Option<String> {
+    let mut content = String::new();
+    entry
+        .take(MAX_ARCHIVE_TEXT_ENTRY_BYTES)
+        .read_to_string(&mut content)
+        .ok()?;
+    Some(content)
+}
+
+fn parse_directives(content: &str) -> (PopcornDirectives, bool) {
     let mut gpus: Vec<String> = Vec::new();
     let mut leaderboard_name = String::new();
     let mut has_multiple_gpus = false;
@@ -60,13 +213,52 @@ pub fn get_popcorn_directives<P: AsRef<Path>>(filepath: P) -> Result<(PopcornDir
         gpus = vec![gpus[0].clone()];
     }
 
-    Ok((
+    (
         PopcornDirectives {
             leaderboard_name,
             gpus,
         },
         has_multiple_gpus,
-    ))
+    )
+}
+
+/// Render a duration given in nanoseconds with a readable unit (ns, µs, ms, s).
+pub fn format_duration_ns(nanos: f64) -> String {
+    let abs = nanos.abs();
+    if abs >= 1e9 {
+        format!("{:.3} s", nanos / 1e9)
+    } else if abs >= 1e6 {
+        format!("{:.3} ms", nanos / 1e6)
+    } else if abs >= 1e3 {
+        format!("{:.2} µs", nanos / 1e3)
+    } else {
+        format!("{:.0} ns", nanos)
+    }
+}
+
+/// Render a byte count with a binary unit (B, KiB, MiB, GiB).
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Geometric mean of strictly positive values, or `None` if there are none
+/// or any value is not positive.
+pub fn geometric_mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() || values.iter().any(|v| *v <= 0.0 || !v.is_finite()) {
+        return None;
+    }
+    let log_sum: f64 = values.iter().map(|v| v.ln()).sum();
+    Some((log_sum / values.len() as f64).exp())
 }
 
 pub fn get_ascii_art_frame(frame: u16) -> String {
@@ -292,6 +484,155 @@ mod tests {
         assert!(result.is_err());
     }
 
+    // Tests for archive directives
+
+    fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+        let mut writer = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in entries {
+            writer
+                .start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    fn write_tar_gz(path: &Path, entries: &[(&str, &str)]) {
+        let encoder =
+            flate2::write::GzEncoder::new(File::create(path).unwrap(), Default::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, content) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn test_zip_manifest_directives() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("solution.zip");
+        write_zip(
+            &path,
+            &[
+                ("popcorn.yaml", "leaderboard: amd-fp8-mm\ngpu: MI300\n"),
+                ("kernel.cu", "__global__ void k() {}"),
+            ],
+        );
+
+        let (directives, has_multiple_gpus) = get_popcorn_directives(&path).unwrap();
+
+        assert_eq!(directives.leaderboard_name, "amd-fp8-mm");
+        assert_eq!(directives.gpus, vec!["MI300"]);
+        assert!(!has_multiple_gpus);
+    }
+
+    #[test]
+    fn test_tar_gz_submission_directives() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("solution.tar.gz");
+        write_tar_gz(
+            &path,
+            &[(
+                "./submission.py",
+                "#!POPCORN leaderboard grayscale_v2\n#!POPCORN gpu A100\n",
+            )],
+        );
+
+        let (directives, has_multiple_gpus) = get_popcorn_directives(&path).unwrap();
+
+        assert_eq!(directives.leaderboard_name, "grayscale_v2");
+        assert_eq!(directives.gpus, vec!["A100"]);
+        assert!(!has_multiple_gpus);
+    }
+
+    #[test]
+    fn test_archive_manifest_overrides_submission_directives() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("solution.zip");
+        write_zip(
+            &path,
+            &[
+                (
+                    "submission.py",
+                    "#!POPCORN leaderboard from-source\n#!POPCORN gpu H100\n",
+                ),
+                ("popcorn.yaml", "gpus: [B200, H100]\n"),
+            ],
+        );
+
+        let (directives, has_multiple_gpus) = get_popcorn_directives(&path).unwrap();
+
+        // The manifest sets GPUs only, so the leaderboard comes from submission.py.
+        assert_eq!(directives.leaderboard_name, "from-source");
+        assert_eq!(directives.gpus, vec!["B200"]);
+        assert!(has_multiple_gpus);
+    }
+
+    #[test]
+    fn test_archive_ignores_nested_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("solution.zip");
+        write_zip(
+            &path,
+            &[
+                ("nested/popcorn.yaml", "leaderboard: nested\n"),
+                ("nested/submission.py", "#!POPCORN gpu H100\n"),
+            ],
+        );
+
+        let (directives, has_multiple_gpus) = get_popcorn_directives(&path).unwrap();
+
+        assert_eq!(directives.leaderboard_name, "");
+        assert!(directives.gpus.is_empty());
+        assert!(!has_multiple_gpus);
+    }
+
+    #[test]
+    fn test_archive_invalid_manifest_returns_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("solution.zip");
+        write_zip(&path, &[("popcorn.yaml", "leaderboard: [unclosed\n")]);
+
+        let Err(err) = get_popcorn_directives(&path) else {
+            panic!("expected an invalid manifest error");
+        };
+
+        assert!(err.to_string().contains("popcorn.yaml"));
+    }
+
+    // Tests for format_duration_ns and geometric_mean
+
+    #[test]
+    fn test_format_duration_ns_picks_unit() {
+        assert_eq!(format_duration_ns(850.0), "850 ns");
+        assert_eq!(format_duration_ns(1520.5), "1.52 µs");
+        assert_eq!(format_duration_ns(2_500_000.0), "2.500 ms");
+        assert_eq!(format_duration_ns(3e9), "3.000 s");
+        assert_eq!(format_duration_ns(-1520.5), "-1.52 µs");
+    }
+
+    #[test]
+    fn test_format_bytes_picks_unit() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(40 * 1024 * 1024), "40.0 MiB");
+        assert_eq!(format_bytes(3 << 30), "3.0 GiB");
+    }
+
+    #[test]
+    fn test_geometric_mean() {
+        let mean = geometric_mean(&[2.0, 8.0]).unwrap();
+        assert!((mean - 4.0).abs() < 1e-12);
+        assert_eq!(geometric_mean(&[]), None);
+        assert_eq!(geometric_mean(&[1.0, 0.0]), None);
+    }
+
     // Tests for custom_wrap
 
     #[test]
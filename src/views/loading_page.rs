@@ -1,83 +1,281 @@
 use ratatui::{
     buffer::Buffer,
-    layout::{Alignment, Layout, Rect},
-    style::{Color, Stylize},
-    widgets::{Block, Gauge, Padding, Paragraph, StatefulWidget, Widget},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, StatefulWidget, Widget},
 };
 
+use std::path::PathBuf;
+
+use super::output_line;
+use crate::service::SubmissionEvent;
+
+/// The log pane keeps this many lines; older output scrolls away.
+const MAX_LOG_LINES: usize = 5000;
+
 #[derive(Debug, Default, Clone)]
 pub struct LoadingPageState {
-    pub loop_count: u16,
-    pub progress_column: u16,
-    pub progress_bar: f64,
+    /// Submission (or Brev job) id once the server accepted the upload.
+    pub submission_id: Option<String>,
+    /// Latest job status, e.g. `pending` or `running`.
+    pub job_status: Option<String>,
+    pub elapsed_secs: u64,
+    /// Queue position and ETA, while the runner reports a queue.
+    pub queue_line: Option<String>,
+    /// Countdown to the leaderboard deadline.
+    pub deadline_line: Option<String>,
+    /// Prompt or note under the header, e.g. the cancel prompt or the state
+    /// of the current `--watch` run.
+    pub status_line: Option<String>,
+    /// Progress log, oldest line first.
+    pub log: Vec<String>,
+    /// How far the log is scrolled up from its end; 0 follows new output.
+    pub log_scroll: u16,
+    /// Log lines that fit in the pane, as of the last render.
+    pub log_rows: u16,
+    /// Where the latest run's stdout, stderr and result files were saved.
+    pub logs_dir: Option<PathBuf>,
 }
 
-#[derive(Default, Debug, PartialEq, Eq, Clone)]
-pub struct LoadingPage {
-    header_area: Rect,
-    gauge_area: Rect,
-    footer_area: Rect,
-}
+impl LoadingPageState {
+    /// Reset the header for a new submission, keeping earlier runs' log
+    /// under a `title` separator.
+    pub fn start_run(&mut self, title: &str) {
+        self.submission_id = None;
+        self.job_status = None;
+        self.queue_line = None;
+        self.logs_dir = None;
+        self.elapsed_secs = 0;
+        if !self.log.is_empty() {
+            self.push_log(String::new());
+        }
+        self.push_log(format!("--- {} ---", title));
+    }
+
+    /// Fold a progress event into the header and log.
+    pub fn record_event(&mut self, event: &SubmissionEvent, elapsed_secs: u64) {
+        match event {
+            SubmissionEvent::Accepted { id } => self.submission_id = Some(id.clone()),
+            SubmissionEvent::LogsSaved { dir, .. } => self.logs_dir = Some(dir.clone()),
+            SubmissionEvent::StatusChanged { status } => self.job_status = Some(status.clone()),
+            SubmissionEvent::Queued { position, .. } => {
+                self.queue_line = (*position > 0).then(|| event.to_string());
+                // The header keeps the queue current without flooding the log.
+                return;
+            }
+            SubmissionEvent::Finished => self.job_status = Some("finished".to_string()),
+            SubmissionEvent::Failed { .. } => self.job_status = Some("failed".to_string()),
+            _ => {}
+        }
+        if let Some(text) = event.progress_text(elapsed_secs) {
+            for line in text.lines() {
+                self.push_log(line.to_string());
+            }
+        }
+    }
+
+    /// Scroll towards older output, stopping once the first line is at the
+    /// top of the pane.
+    pub fn scroll_up(&mut self, lines: u16) {
+        self.log_scroll = self.log_scroll.saturating_add(lines).min(self.max_scroll());
+    }
 
-fn get_gradient_color(progress: f64) -> Color {
-    // Convert progress from 0-100 to 0-1
-    let t = progress / 100.0;
+    pub fn scroll_down(&mut self, lines: u16) {
+        self.log_scroll = self.log_scroll.saturating_sub(lines);
+    }
 
-    // Start with red (255, 0, 0) and end with green (0, 255, 0)
-    let r = ((1.0 - t) * 255.0) as u8;
-    let g = (t * 255.0) as u8;
-    let b = 0;
+    fn max_scroll(&self) -> u16 {
+        let scrollable = self.log.len().saturating_sub(usize::from(self.log_rows));
+        u16::try_from(scrollable).unwrap_or(u16::MAX)
+    }
 
-    Color::Rgb(r, g, b)
+    fn push_log(&mut self, line: String) {
+        self.log.push(line);
+        if self.log.len() > MAX_LOG_LINES {
+            self.log.drain(..self.log.len() - MAX_LOG_LINES);
+        }
+        // Keep a scrolled-up view on the same lines while output arrives.
+        if self.log_scroll > 0 {
+            self.log_scroll = self.log_scroll.saturating_add(1).min(self.max_scroll());
+        }
+    }
 }
 
+#[derive(Default, Debug, PartialEq, Eq, Clone)]
+pub struct LoadingPage {}
+
 impl StatefulWidget for &LoadingPage {
     type State = LoadingPageState;
 
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        use ratatui::layout::Constraint::Percentage;
+        let header = header_lines(state);
+        let header_height = u16::try_from(header.len()).unwrap_or(u16::MAX) + 2;
+        let layout = Layout::vertical([Constraint::Length(header_height), Constraint::Min(3)]);
+        let [header_area, log_area] = layout.areas(area);
 
-        let layout = Layout::vertical([Percentage(45), Percentage(10), Percentage(45)]);
+        Paragraph::new(header)
+            .block(Block::default().borders(Borders::ALL).title("Submission"))
+            .render(header_area, buf);
+        render_log(log_area, buf, state);
+    }
+}
 
-        let [_, gauge_area, footer_area] = layout.areas(area);
+fn header_lines(state: &LoadingPageState) -> Vec<Line<'_>> {
+    let id = match &state.submission_id {
+        Some(id) => format!("Submission {}", id),
+        None => "Submitting".to_string(),
+    };
+    let status = state.job_status.as_deref().unwrap_or("uploading");
+    let mut lines = vec![Line::from(vec![
+        Span::raw(id).bold(),
+        Span::raw("  "),
+        Span::styled(status, Style::new().fg(status_color(status))),
+        Span::raw(format!("  {}", format_elapsed(state.elapsed_secs))),
+    ])];
+    if let Some(queue_line) = &state.queue_line {
+        lines.push(Line::raw(queue_line.as_str()));
+    }
+    if let Some(deadline_line) = &state.deadline_line {
+        lines.push(Line::styled(
+            deadline_line.as_str(),
+            Style::new().fg(Color::Cyan),
+        ));
+    }
+    if let Some(status_line) = &state.status_line {
+        lines.push(Line::styled(
+            status_line.as_str(),
+            Style::new().fg(Color::Yellow),
+        ));
+    }
+    lines
+}
 
-        render_gauge(gauge_area, buf, state);
-        render_footer(footer_area, buf, state);
+fn status_color(status: &str) -> Color {
+    match status {
+        "finished" | "done" | "succeeded" => Color::Green,
+        "failed" | "timed_out" | "hacked" => Color::Red,
+        "running" => Color::Yellow,
+        _ => Color::White,
     }
 }
 
-fn render_gauge(area: Rect, buf: &mut Buffer, state: &mut LoadingPageState) {
-    let blk = Block::default().padding(Padding::horizontal(20));
-    Gauge::default()
-        .block(blk)
-        .gauge_style(get_gradient_color(state.progress_bar))
-        .ratio(state.progress_bar / 100.0)
+fn render_log(area: Rect, buf: &mut Buffer, state: &mut LoadingPageState) {
+    state.log_rows = area.height.saturating_sub(2);
+    state.log_scroll = state.log_scroll.min(state.max_scroll());
+    let visible = usize::from(state.log_rows);
+    let end = state
+        .log
+        .len()
+        .saturating_sub(usize::from(state.log_scroll));
+    let start = end.saturating_sub(visible);
+    let lines: Vec<Line> = state.log[start..end]
+        .iter()
+        .map(|line| output_line(line.as_str()))
+        .collect();
+    let title = if state.log_scroll > 0 {
+        format!("Log (scrolled up {} lines)", state.log_scroll)
+    } else {
+        "Log".to_string()
+    };
+    Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_bottom("↑/↓ PgUp/PgDn scroll · End follow · q cancel"),
+        )
         .render(area, buf);
 }
 
-fn get_footer_text(state: &LoadingPageState) -> String {
-    let percentage = state.progress_bar;
-
-    if state.loop_count > 0 {
-        return "Did you know we have zero idea how long this will take?".to_string();
-    }
-
-    if percentage > 75.0 {
-        "Almost there!".to_string()
-    } else if percentage > 35.0 {
-        "Crunching numbers...".to_string()
+fn format_elapsed(secs: u64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
     } else {
-        "This is taking a while, huh?".to_string()
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
     }
 }
 
-fn render_footer(area: Rect, buf: &mut Buffer, state: &LoadingPageState) {
-    let blk = Block::default().padding(Padding::vertical(1));
-    let text = Paragraph::new(get_footer_text(state))
-        .alignment(Alignment::Center)
-        .fg(Color::White)
-        .bold()
-        .block(blk);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_fill_header_and_log() {
+        let mut state = LoadingPageState::default();
+        state.start_run("Submitting");
+        state.record_event(
+            &SubmissionEvent::Accepted {
+                id: "42".to_string(),
+            },
+            0,
+        );
+        state.record_event(
+            &SubmissionEvent::Queued {
+                runner: Some("Modal B200".to_string()),
+                position: 3,
+                eta_seconds: None,
+            },
+            5,
+        );
+        state.record_event(
+            &SubmissionEvent::Stdout {
+                run: Some(1),
+                text: "line one\nline two".to_string(),
+            },
+            9,
+        );
+
+        assert_eq!(state.submission_id.as_deref(), Some("42"));
+        assert_eq!(
+            state.queue_line.as_deref(),
+            Some("Modal B200 queue: 3 queued jobs")
+        );
+        assert_eq!(
+            state.log,
+            vec![
+                "--- Submitting ---",
+                "Submission 42 accepted. Waiting for results...",
+                "STDOUT (Run 1):",
+                "line one",
+                "line two",
+            ]
+        );
 
-    text.render(area, buf);
+        state.scroll_up(100);
+        assert_eq!(state.log_scroll, 5);
+        state.scroll_down(2);
+        assert_eq!(state.log_scroll, 3);
+    }
+
+    #[test]
+    fn test_scroll_is_clamped_and_holds_position() {
+        let mut state = LoadingPageState {
+            log: (0..10).map(|i| i.to_string()).collect(),
+            log_rows: 4,
+            ..Default::default()
+        };
+
+        state.scroll_up(100);
+        assert_eq!(state.log_scroll, 6);
+
+        state.scroll_down(3);
+        state.push_log("10".to_string());
+        state.push_log("11".to_string());
+        // Still showing lines 2..6.
+        assert_eq!(state.log_scroll, 5);
+
+        state.scroll_down(u16::MAX);
+        state.push_log("12".to_string());
+        assert_eq!(state.log_scroll, 0);
+    }
+
+    #[test]
+    fn test_format_elapsed() {
+        assert_eq!(format_elapsed(7), "7s");
+        assert_eq!(format_elapsed(65), "1m 05s");
+        assert_eq!(format_elapsed(3720), "1h 02m");
+    }
 }
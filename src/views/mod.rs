@@ -1,2 +1,20 @@
+use std::borrow::Cow;
+
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::Line;
+
+use crate::service::FIRST_FAILURE_MARKER;
+
 pub mod loading_page;
 pub mod result_page;
+
+/// A line of submission output, with the first failing test of a failure
+/// table highlighted.
+fn output_line<'a>(line: impl Into<Cow<'a, str>>) -> Line<'a> {
+    let line = line.into();
+    if line.starts_with(FIRST_FAILURE_MARKER) {
+        Line::styled(line, Style::new().fg(Color::Red).bold())
+    } else {
+        Line::raw(line)
+    }
+}
@@ -1,3 +1,4 @@
+use super::output_line;
 use crate::utils;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 use ratatui::{
@@ -5,6 +6,7 @@ use ratatui::{
     prelude::Buffer,
     style::{Color, Style},
     symbols::scrollbar,
+    text::Line,
     widgets::{Block, BorderType, Paragraph, Scrollbar, ScrollbarState, StatefulWidget, Widget},
 };
 
@@ -38,8 +40,13 @@ impl ResultPage {
         state.horizontal_scroll_state = state.horizontal_scroll_state.content_length(max_width);
         state.animation_frame = 0;
 
+        let lines: Vec<Line<'static>> = result_text
+            .lines()
+            .map(|line| output_line(line.to_string()))
+            .collect();
+
         Self {
-            result_text: Paragraph::new(result_text),
+            result_text: Paragraph::new(lines),
         }
     }
 